@@ -0,0 +1,71 @@
+use crate::{
+    hittable::{HitRecord, Hittable},
+    integrator::Integrator,
+    onb::Onb,
+    ray::Ray,
+    sampler::SampleStream,
+    scene::Scene,
+    stats,
+    vec3::{Color, Vec3},
+};
+
+/// Shades every surface by how much of its hemisphere is open, ignoring materials and lights.
+///
+/// Useful for quick previews and clay renders. Rays that leave the scene are white.
+#[derive(Debug, Clone, Copy)]
+pub struct AmbientOcclusion {
+    distance: f64,
+    samples: usize,
+}
+
+impl AmbientOcclusion {
+    /// Geometry further away than `distance` does not occlude, `samples` rays are traced
+    /// per camera ray.
+    pub fn new(distance: f64, samples: usize) -> Self {
+        Self {
+            distance,
+            samples: samples.max(1),
+        }
+    }
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> Self {
+        Self::new(2.0, 4)
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn li(&self, r: Ray, scene: &Scene, _max_depth: usize, samples: &mut SampleStream) -> Color {
+        let mut rec = HitRecord::default();
+        if scene
+            .world()
+            .hit(r, 0.001, f64::INFINITY, &mut rec)
+            .is_miss()
+        {
+            return Vec3(1.0, 1.0, 1.0);
+        }
+
+        // with cosine weighted directions the estimate is just the fraction of open rays
+        samples.start_bounce(0);
+        let basis = Onb::from_w(rec.normal);
+        let mut open = 0;
+        for _ in 0..self.samples {
+            let direction = basis.local(Vec3::sample_cosine_direction(samples.get_2d()));
+
+            stats::count_shadow_ray();
+            let occlusion_ray = Ray::new(rec.p, direction, r.time());
+            let mut occluder = HitRecord::default();
+            if scene
+                .world()
+                .hit(occlusion_ray, 0.001, self.distance, &mut occluder)
+                .is_miss()
+            {
+                open += 1;
+            }
+        }
+
+        let visibility = open as f64 / self.samples as f64;
+        Vec3(visibility, visibility, visibility)
+    }
+}
@@ -0,0 +1,132 @@
+use std::ops;
+
+use crate::vec3::{Color, Vec3};
+
+/// Arbitrary output variable, a render layer recorded next to the final color.
+///
+/// The light layers add up to the final color: emission seen directly by the camera, light
+/// arriving at the first hit straight from a light (direct) or after further bounces
+/// (indirect), split by whether the first hit reflected it diffusely or specularly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aov {
+    /// Reflectance of the first surface hit
+    Albedo,
+    /// World space shading normal at the first hit
+    Normal,
+    /// Distance from the camera to the first hit, zero where nothing was hit
+    Depth,
+    DirectDiffuse,
+    IndirectDiffuse,
+    DirectSpecular,
+    IndirectSpecular,
+    /// Lights, emissive surfaces and the background seen directly by the camera
+    Emission,
+}
+
+impl Aov {
+    pub const COUNT: usize = 8;
+
+    pub const ALL: [Aov; Aov::COUNT] = [
+        Aov::Albedo,
+        Aov::Normal,
+        Aov::Depth,
+        Aov::DirectDiffuse,
+        Aov::IndirectDiffuse,
+        Aov::DirectSpecular,
+        Aov::IndirectSpecular,
+        Aov::Emission,
+    ];
+
+    /// Name of the layer in multi-layer output files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Albedo => "albedo",
+            Aov::Normal => "normal",
+            Aov::Depth => "depth",
+            Aov::DirectDiffuse => "diffuse_direct",
+            Aov::IndirectDiffuse => "diffuse_indirect",
+            Aov::DirectSpecular => "specular_direct",
+            Aov::IndirectSpecular => "specular_indirect",
+            Aov::Emission => "emission",
+        }
+    }
+
+    /// Channel names of the layer, depth only uses the first component of its value.
+    pub fn channels(&self) -> &'static [&'static str] {
+        match self {
+            Aov::Normal => &["X", "Y", "Z"],
+            Aov::Depth => &["Z"],
+            _ => &["R", "G", "B"],
+        }
+    }
+
+    /// Layer that receives light arriving over a path, `bounce` being the number of
+    /// surfaces it was reflected by before reaching the camera.
+    pub(crate) fn for_light(bounce: usize, diffuse: bool) -> Aov {
+        match (bounce, diffuse) {
+            (0, _) => Aov::Emission,
+            (1, true) => Aov::DirectDiffuse,
+            (1, false) => Aov::DirectSpecular,
+            (_, true) => Aov::IndirectDiffuse,
+            (_, false) => Aov::IndirectSpecular,
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Values of all [`Aov`]s for one pixel or sample.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Aovs {
+    values: [Color; Aov::COUNT],
+}
+
+impl Aovs {
+    pub fn get(&self, aov: Aov) -> Color {
+        self.values[aov.index()]
+    }
+
+    pub fn set(&mut self, aov: Aov, value: Color) {
+        self.values[aov.index()] = value;
+    }
+
+    pub fn add(&mut self, aov: Aov, value: Color) {
+        self.values[aov.index()] += value;
+    }
+
+    pub(crate) fn set_depth(&mut self, depth: f64) {
+        self.set(Aov::Depth, Vec3(depth, depth, depth));
+    }
+
+    /// Replaces negative values with zero in every layer that can't be negative, which is
+    /// all of them except the normal.
+    pub(crate) fn max_zero(mut self) -> Self {
+        for aov in Aov::ALL {
+            if aov != Aov::Normal {
+                self.values[aov.index()] = self.get(aov).max_zero();
+            }
+        }
+        self
+    }
+}
+
+impl ops::AddAssign<Aovs> for Aovs {
+    fn add_assign(&mut self, rhs: Aovs) {
+        for (value, rhs) in self.values.iter_mut().zip(rhs.values) {
+            *value += rhs;
+        }
+    }
+}
+
+impl ops::Mul<f64> for Aovs {
+    type Output = Aovs;
+
+    fn mul(mut self, rhs: f64) -> Self::Output {
+        for value in self.values.iter_mut() {
+            *value *= rhs;
+        }
+        self
+    }
+}
@@ -0,0 +1,227 @@
+use std::sync::OnceLock;
+
+use crate::sampler::{hash, mix, pixel_coordinates, to_unit, Sampler};
+
+/// Generating vector of an extensible rank-1 lattice in base 2, one entry per dimension.
+/// Beyond it the sampler falls back to random numbers.
+const GENERATOR: [u32; 32] = [
+    1, 182667, 469891, 498753, 110745, 446247, 250185, 118627, 245333, 283199, 408519, 391023,
+    246327, 126539, 399185, 461527, 300343, 69681, 516695, 436179, 106383, 238523, 413283, 70841,
+    47719, 300129, 113029, 123925, 410745, 211325, 17489, 511893,
+];
+
+/// Side of the square blue noise tile, repeated over the image
+const TILE_SIZE: usize = 64;
+
+/// Width of the gaussian that void-and-cluster uses to find clusters and voids
+const TILE_SIGMA: f64 = 1.5;
+
+/// Rank-1 lattice whose points every pixel shifts by a value from a blue noise tile.
+///
+/// Neighbouring pixels get very different shifts, so at low sample counts the error looks
+/// like fine grained blue noise instead of clumps, which is easier on the eye and easier to
+/// denoise. Every dimension reads the tile at its own offset.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlueNoiseSampler;
+
+impl Sampler for BlueNoiseSampler {
+    fn get_1d(&self, seed: u64, index: usize, _count: usize, dimension: usize) -> f64 {
+        if dimension >= GENERATOR.len() {
+            return to_unit(mix(mix(seed, index as u64), dimension as u64));
+        }
+
+        // taking the points in radical inverse order makes every power of two prefix a
+        // complete lattice
+        let point = (index as u32)
+            .reverse_bits()
+            .wrapping_mul(GENERATOR[dimension]);
+        let value = point.wrapping_add(shift(seed, dimension));
+        value as f64 / (1u64 << 32) as f64
+    }
+}
+
+/// Blue noise value of the pixel for `dimension`, as a fraction of `2^32`.
+fn shift(seed: u64, dimension: usize) -> u32 {
+    let (i, j) = pixel_coordinates(seed);
+    let offset = hash(dimension as u64);
+    let tile_size = TILE_SIZE as u64;
+    let x = ((i as u64 + offset % tile_size) % tile_size) as usize;
+    let y = ((j as u64 + (offset >> 32) % tile_size) % tile_size) as usize;
+
+    let rank = tile()[y * TILE_SIZE + x] as u64;
+    (((2 * rank + 1) << 32) / (2 * (TILE_SIZE * TILE_SIZE) as u64)) as u32
+}
+
+/// Ranks `0..TILE_SIZE^2` arranged as blue noise, generated on first use.
+fn tile() -> &'static [u16] {
+    static TILE: OnceLock<Vec<u16>> = OnceLock::new();
+    TILE.get_or_init(void_and_cluster)
+}
+
+/// Void-and-cluster after Ulichney: starting from a relaxed pattern, points are ranked by
+/// removing the tightest cluster or filling the largest void, one at a time.
+fn void_and_cluster() -> Vec<u16> {
+    let len = TILE_SIZE * TILE_SIZE;
+    let mut pattern = Pattern::new();
+    for cell in 0..len {
+        if hash(cell as u64) % 10 == 0 {
+            pattern.toggle(cell);
+        }
+    }
+
+    // swap points from clusters into voids until the pattern is evenly spread
+    for _ in 0..len {
+        let cluster = pattern.tightest_cluster();
+        pattern.toggle(cluster);
+        let void = pattern.largest_void();
+        pattern.toggle(void);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut ranks = vec![0; len];
+    let initial = pattern.clone();
+    let ones = pattern.count;
+    for rank in (0..ones).rev() {
+        let cluster = pattern.tightest_cluster();
+        pattern.toggle(cluster);
+        ranks[cluster] = rank as u16;
+    }
+
+    pattern = initial;
+    for rank in ones..len {
+        let void = pattern.largest_void();
+        pattern.toggle(void);
+        ranks[void] = rank as u16;
+    }
+
+    ranks
+}
+
+/// Binary pattern on the tile together with the gaussian filtered density of its points.
+#[derive(Clone)]
+struct Pattern {
+    points: Vec<bool>,
+    energy: Vec<f64>,
+    kernel: Vec<f64>,
+    count: usize,
+}
+
+impl Pattern {
+    fn new() -> Self {
+        let len = TILE_SIZE * TILE_SIZE;
+        let mut kernel = vec![0.0; len];
+        for y in 0..TILE_SIZE {
+            for x in 0..TILE_SIZE {
+                // distances wrap around, so the tile repeats without seams
+                let dx = usize::min(x, TILE_SIZE - x) as f64;
+                let dy = usize::min(y, TILE_SIZE - y) as f64;
+                kernel[y * TILE_SIZE + x] =
+                    (-(dx * dx + dy * dy) / (2.0 * TILE_SIGMA * TILE_SIGMA)).exp();
+            }
+        }
+
+        Self {
+            points: vec![false; len],
+            energy: vec![0.0; len],
+            kernel,
+            count: 0,
+        }
+    }
+
+    fn toggle(&mut self, cell: usize) {
+        let sign = if self.points[cell] { -1.0 } else { 1.0 };
+        self.points[cell] = !self.points[cell];
+        if self.points[cell] {
+            self.count += 1;
+        } else {
+            self.count -= 1;
+        }
+
+        let (cx, cy) = (cell % TILE_SIZE, cell / TILE_SIZE);
+        for y in 0..TILE_SIZE {
+            let ky = (y + TILE_SIZE - cy) % TILE_SIZE;
+            for x in 0..TILE_SIZE {
+                let kx = (x + TILE_SIZE - cx) % TILE_SIZE;
+                self.energy[y * TILE_SIZE + x] += sign * self.kernel[ky * TILE_SIZE + kx];
+            }
+        }
+    }
+
+    /// Point with the most points around it.
+    fn tightest_cluster(&self) -> usize {
+        self.extreme(true, |a, b| a > b)
+    }
+
+    /// Empty cell with the fewest points around it.
+    fn largest_void(&self) -> usize {
+        self.extreme(false, |a, b| a < b)
+    }
+
+    fn extreme(&self, point: bool, better: impl Fn(f64, f64) -> bool) -> usize {
+        let mut best = None;
+        for (cell, &energy) in self.energy.iter().enumerate() {
+            if self.points[cell] != point {
+                continue;
+            }
+            match best {
+                Some((_, best_energy)) if !better(energy, best_energy) => {}
+                _ => best = Some((cell, energy)),
+            }
+        }
+
+        best.map_or(0, |(cell, _)| cell)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampler::{checks, pixel_seed};
+
+    #[test]
+    fn shifts_are_deterministic_per_pixel() {
+        for (i, j) in [(0, 0), (17, 3), (63, 63), (1000, 500)] {
+            for dimension in [0, 1, 7] {
+                let seed = pixel_seed(i, j);
+                assert_eq!(shift(seed, dimension), shift(seed, dimension));
+                let value = BlueNoiseSampler.get_1d(seed, 5, 16, dimension);
+                assert_eq!(value, BlueNoiseSampler.get_1d(seed, 5, 16, dimension));
+
+                // the tile repeats over the image
+                let repeated = pixel_seed(i + TILE_SIZE, j + 2 * TILE_SIZE);
+                assert_eq!(shift(seed, dimension), shift(repeated, dimension));
+            }
+        }
+
+        assert_ne!(shift(pixel_seed(0, 0), 0), shift(pixel_seed(1, 0), 0));
+        assert_ne!(shift(pixel_seed(0, 0), 0), shift(pixel_seed(0, 0), 1));
+    }
+
+    #[test]
+    fn tile_ranks_every_cell_once() {
+        let mut ranks = tile().to_vec();
+        ranks.sort_unstable();
+        assert!(ranks
+            .iter()
+            .enumerate()
+            .all(|(i, &rank)| rank as usize == i));
+    }
+
+    #[test]
+    fn every_stratum_gets_one_sample() {
+        for seed in [pixel_seed(0, 0), pixel_seed(17, 3)] {
+            for k in 0..10 {
+                for dimension in 0..8 {
+                    assert!(checks::covers_strata(
+                        &BlueNoiseSampler,
+                        seed,
+                        1 << k,
+                        dimension
+                    ));
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     ray::Ray,
-    utils::random_in_range,
+    sampler::SampleStream,
     vec3::{Point3, Vec3},
 };
 
@@ -19,6 +19,7 @@ pub struct Camera {
 }
 
 impl Camera {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         lookfrom: Point3,
         lookat: Point3,
@@ -59,14 +60,16 @@ impl Camera {
         }
     }
 
-    pub fn get_ray(&self, s: f64, t: f64) -> Ray {
-        let rd = self.lens_radius * Vec3::random_in_unit_disk();
+    /// Ray through the point `(s, t)` of the viewport, the position on the lens and the time
+    /// are taken from `samples`.
+    pub fn get_ray(&self, s: f64, t: f64, samples: &mut SampleStream) -> Ray {
+        let rd = self.lens_radius * Vec3::sample_in_unit_disk(samples.get_2d());
         let offset = self.u * rd.x() + self.v * rd.y();
 
         Ray::new(
             self.origin + offset,
             self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset,
-            random_in_range(self.time_0, self.time_1),
+            self.time_0 + samples.get_1d() * (self.time_1 - self.time_0),
         )
     }
 }
@@ -0,0 +1,247 @@
+use std::sync::Arc;
+
+use crate::{
+    dielectric::absorption_for_color,
+    fresnel,
+    hittable::HitRecord,
+    material::{local_directions, Material, ScatterRecord},
+    microfacet::TrowbridgeReitz,
+    onb::Onb,
+    ray::Ray,
+    sampler::SampleStream,
+    vec3::{Color, Vec3},
+};
+
+/// Clear coat of varnish or lacquer over any other material, e.g. car paint over a
+/// [`crate::Lambertian`] or [`crate::Metal`].
+///
+/// The coat is a dielectric layer that reflects at its surface and optionally tints the
+/// light passing through it to the base and back, more so the thicker it is. Light is
+/// assumed to reach the base without being bent by the coat and not to bounce between the
+/// layers, which keeps the base evaluable. The base is treated as opaque, rays hitting the
+/// surface from the inside only see the base.
+pub struct Coated {
+    base: Arc<dyn Material>,
+    ir: f64,           // index of refraction
+    absorption: Color, // per unit of distance traveled inside the coat
+    thickness: f64,
+    distribution: TrowbridgeReitz,
+}
+
+impl Coated {
+    /// Smooth, clear coat with the index of refraction of varnish, a millimetre thick in a
+    /// scene measured in metres.
+    pub fn new(base: Arc<dyn Material>) -> Self {
+        Self {
+            base,
+            ir: 1.5,
+            absorption: Color::default(),
+            thickness: 0.001,
+            distribution: TrowbridgeReitz::new(0.0),
+        }
+    }
+
+    pub fn with_ir(mut self, ir: f64) -> Self {
+        self.ir = ir.max(1.0);
+        self
+    }
+
+    /// Roughness of the coat surface, zero gives mirror like reflections.
+    pub fn with_roughness(mut self, roughness: f64) -> Self {
+        self.distribution = TrowbridgeReitz::new(roughness);
+        self
+    }
+
+    /// Thickness of the coat in the units of the scene. The light passing through a thicker
+    /// coat is absorbed more, and more so at grazing angles, where its path is longer.
+    pub fn with_thickness(mut self, thickness: f64) -> Self {
+        self.thickness = thickness.max(0.0);
+        self
+    }
+
+    /// See [`crate::Dielectric::with_absorption`].
+    pub fn with_absorption(mut self, absorption: Color) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// See [`crate::Dielectric::with_color_at_distance`], with a distance of the thickness
+    /// the coat takes this color when passing straight through it once.
+    pub fn with_color_at_distance(mut self, color: Color, distance: f64) -> Self {
+        self.absorption = absorption_for_color(color, distance);
+        self
+    }
+
+    /// Probability of sampling the coat instead of the base.
+    fn coat_probability(&self, wo: Vec3) -> f64 {
+        fresnel::dielectric(wo.z(), self.ir)
+    }
+
+    /// Part of the light that makes it through the coat to the base and back, entering
+    /// from `wi` and leaving towards `wo`.
+    fn through_coat(&self, wo: Vec3, wi: Vec3) -> Color {
+        let transmitted = (1.0 - fresnel::dielectric(wo.z(), self.ir))
+            * (1.0 - fresnel::dielectric(wi.z(), self.ir));
+
+        // length of the path through the coat down to the base and back up
+        let refracted_cos = |cos: f64| (1.0 - (1.0 - cos * cos) / (self.ir * self.ir)).sqrt();
+        let length = self.thickness * (1.0 / refracted_cos(wo.z()) + 1.0 / refracted_cos(wi.z()));
+        let a = self.absorption;
+
+        transmitted
+            * Vec3(
+                (-a.x() * length).exp(),
+                (-a.y() * length).exp(),
+                (-a.z() * length).exp(),
+            )
+    }
+}
+
+impl Material for Coated {
+    fn scatter(
+        &self,
+        r_in: Ray,
+        rec: &HitRecord,
+        samples: &mut SampleStream,
+    ) -> Option<ScatterRecord> {
+        let basis = Onb::from_w(rec.normal);
+        let wo = basis.to_local(-Vec3::unit_vector(r_in.direction()));
+        if !rec.front_face() || wo.z() <= 0.0 {
+            return self.base.scatter(r_in, rec, samples);
+        }
+
+        let coat_probability = self.coat_probability(wo);
+        let direction = if samples.get_1d() < coat_probability {
+            if self.distribution.is_smooth() {
+                // the probability already is the reflectance
+                return Some(ScatterRecord {
+                    direction: basis.local(Vec3(-wo.x(), -wo.y(), wo.z())),
+                    bsdf: Vec3(1.0, 1.0, 1.0),
+                    pdf: 1.0,
+                    specular: true,
+                });
+            }
+
+            let h = self
+                .distribution
+                .sample_visible_normal(wo, samples.get_2d());
+            basis.local(Vec3::reflect(-wo, h))
+        } else {
+            let scatter = self.base.scatter(r_in, rec, samples)?;
+            if scatter.specular {
+                let wi = basis.to_local(scatter.direction);
+                return Some(ScatterRecord {
+                    bsdf: scatter.bsdf * self.through_coat(wo, wi) / (1.0 - coat_probability),
+                    ..scatter
+                });
+            }
+            scatter.direction
+        };
+
+        // the density of both layers together, as either of them could have picked the
+        // direction
+        let pdf = self.pdf(r_in, rec, direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(ScatterRecord {
+            direction,
+            bsdf: self.bsdf(r_in, rec, direction),
+            pdf,
+            specular: false,
+        })
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        self.base.emitted(rec)
+    }
+
+    fn is_specular(&self) -> bool {
+        self.distribution.is_smooth() && self.base.is_specular()
+    }
+
+    fn is_diffuse(&self) -> bool {
+        self.base.is_diffuse()
+    }
+
+    fn bsdf(&self, r_in: Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        let (wo, wi) = local_directions(r_in, rec, direction);
+        let base = self.base.bsdf(r_in, rec, direction);
+        if !rec.front_face() || wo.z() <= 0.0 {
+            return base;
+        }
+        if wi.z() <= 0.0 {
+            return Color::default();
+        }
+
+        let mut value = base * self.through_coat(wo, wi);
+        if !self.distribution.is_smooth() {
+            let h = Vec3::unit_vector(wo + wi);
+            let coat = fresnel::dielectric(Vec3::dot(wo, h), self.ir)
+                * self.distribution.d(h)
+                * self.distribution.g(wo, wi)
+                / (4.0 * wo.z() * wi.z());
+            value += Vec3(coat, coat, coat);
+        }
+
+        value
+    }
+
+    fn pdf(&self, r_in: Ray, rec: &HitRecord, direction: Vec3) -> f64 {
+        let (wo, wi) = local_directions(r_in, rec, direction);
+        let base = self.base.pdf(r_in, rec, direction);
+        if !rec.front_face() || wo.z() <= 0.0 {
+            return base;
+        }
+        if wi.z() <= 0.0 {
+            return 0.0;
+        }
+
+        let coat_probability = self.coat_probability(wo);
+        let mut pdf = (1.0 - coat_probability) * base;
+        if !self.distribution.is_smooth() {
+            let h = Vec3::unit_vector(wo + wi);
+            pdf += coat_probability * self.distribution.visible_normal_pdf(wo, h)
+                / (4.0 * Vec3::dot(wo, h));
+        }
+
+        pdf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lambertian::Lambertian, material::checks};
+
+    fn varnish(thickness: f64) -> Coated {
+        Coated::new(Arc::new(Lambertian::new(Vec3(0.8, 0.8, 0.8))))
+            .with_roughness(0.5)
+            .with_thickness(thickness)
+            .with_color_at_distance(Vec3(0.9, 0.6, 0.3), 0.001)
+    }
+
+    #[test]
+    fn scatter_matches_bsdf() {
+        for wo in checks::VIEW_DIRECTIONS {
+            let (r_in, rec) = checks::hit(wo);
+            let (sampled, uniform) = checks::albedo_estimates(&varnish(0.001), r_in, &rec);
+            assert!(
+                (sampled - uniform).abs() < 0.02,
+                "{wo:?}: {sampled} != {uniform}"
+            );
+        }
+    }
+
+    #[test]
+    fn thicker_coats_absorb_more() {
+        let (wo, wi) = (Vec3(0.0, 0.0, 1.0), Vec3(0.0, 0.6, 0.8));
+        let thin = varnish(0.001).through_coat(wo, wi);
+        let thick = varnish(0.002).through_coat(wo, wi);
+        let clear = varnish(0.0).through_coat(wo, wi);
+
+        assert!(thick.x() < thin.x() && thin.x() < clear.x());
+        assert_eq!(clear.x(), clear.z());
+    }
+}
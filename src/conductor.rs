@@ -0,0 +1,173 @@
+use crate::{
+    fresnel,
+    hittable::HitRecord,
+    material::{local_directions, Material, ScatterRecord},
+    microfacet::TrowbridgeReitz,
+    onb::Onb,
+    ray::Ray,
+    sampler::SampleStream,
+    vec3::{Color, Vec3},
+};
+
+/// Rough metal made of GGX microfacets, colored by the Fresnel reflectance of its complex
+/// index of refraction.
+///
+/// Unlike [`crate::Metal`] it is physically based: the rougher it gets the wider its
+/// highlights spread, without reflecting more light than arrives. A roughness of zero is a
+/// perfect mirror. Light bouncing between the microfacets more than once is lost, so very
+/// rough conductors come out a bit darker than they should.
+#[derive(Debug, Clone, Copy)]
+pub struct Conductor {
+    eta: Color,
+    k: Color,
+    distribution: TrowbridgeReitz,
+}
+
+impl Conductor {
+    /// Conductor with the index of refraction `eta + i k` for red, green and blue light.
+    pub fn new(eta: Color, k: Color, roughness: f64) -> Self {
+        Self {
+            eta,
+            k,
+            distribution: TrowbridgeReitz::new(roughness),
+        }
+    }
+
+    pub fn gold(roughness: f64) -> Self {
+        Self::new(
+            Vec3(0.143, 0.374, 1.442),
+            Vec3(3.983, 2.385, 1.603),
+            roughness,
+        )
+    }
+
+    pub fn copper(roughness: f64) -> Self {
+        Self::new(
+            Vec3(0.200, 0.924, 1.102),
+            Vec3(3.912, 2.452, 2.142),
+            roughness,
+        )
+    }
+
+    pub fn aluminium(roughness: f64) -> Self {
+        Self::new(
+            Vec3(1.657, 0.880, 0.521),
+            Vec3(9.224, 6.270, 4.837),
+            roughness,
+        )
+    }
+
+    pub fn silver(roughness: f64) -> Self {
+        Self::new(
+            Vec3(0.155, 0.117, 0.138),
+            Vec3(4.828, 3.122, 2.147),
+            roughness,
+        )
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(
+        &self,
+        r_in: Ray,
+        rec: &HitRecord,
+        samples: &mut SampleStream,
+    ) -> Option<ScatterRecord> {
+        let basis = Onb::from_w(rec.normal);
+        let wo = basis.to_local(-Vec3::unit_vector(r_in.direction()));
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        if self.distribution.is_smooth() {
+            return Some(ScatterRecord {
+                direction: basis.local(Vec3(-wo.x(), -wo.y(), wo.z())),
+                bsdf: fresnel::conductor(wo.z(), self.eta, self.k),
+                pdf: 1.0,
+                specular: true,
+            });
+        }
+
+        let h = self
+            .distribution
+            .sample_visible_normal(wo, samples.get_2d());
+        let wi = Vec3::reflect(-wo, h);
+        if wi.z() <= 0.0 {
+            return None;
+        }
+
+        let direction = basis.local(wi);
+        let pdf = self.pdf(r_in, rec, direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(ScatterRecord {
+            direction,
+            bsdf: self.bsdf(r_in, rec, direction),
+            pdf,
+            specular: false,
+        })
+    }
+
+    fn is_specular(&self) -> bool {
+        self.distribution.is_smooth()
+    }
+
+    fn bsdf(&self, r_in: Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        let (wo, wi) = local_directions(r_in, rec, direction);
+        if self.is_specular() || wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return Color::default();
+        }
+
+        let h = Vec3::unit_vector(wo + wi);
+        let fresnel = fresnel::conductor(Vec3::dot(wo, h), self.eta, self.k);
+        fresnel * (self.distribution.d(h) * self.distribution.g(wo, wi) / (4.0 * wo.z() * wi.z()))
+    }
+
+    fn pdf(&self, r_in: Ray, rec: &HitRecord, direction: Vec3) -> f64 {
+        let (wo, wi) = local_directions(r_in, rec, direction);
+        if self.is_specular() || wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return 0.0;
+        }
+
+        // reflecting at h doubles the angles, which spreads the density over four times the
+        // solid angle
+        let h = Vec3::unit_vector(wo + wi);
+        self.distribution.visible_normal_pdf(wo, h) / (4.0 * Vec3::dot(wo, h))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::checks;
+
+    #[test]
+    fn pdf_integrates_to_at_most_one() {
+        for roughness in [0.4, 0.7, 1.0] {
+            for wo in checks::VIEW_DIRECTIONS {
+                let (r_in, rec) = checks::hit(wo);
+                let integral = checks::pdf_integral(&Conductor::gold(roughness), r_in, &rec);
+                assert!(integral <= 1.01, "{roughness}: {integral}");
+                assert!(integral > 0.4, "{roughness}: {integral}");
+            }
+        }
+    }
+
+    #[test]
+    fn scatter_matches_bsdf() {
+        for roughness in [0.4, 0.7, 1.0] {
+            for wo in checks::VIEW_DIRECTIONS {
+                let (r_in, rec) = checks::hit(wo);
+                let (sampled, uniform) =
+                    checks::albedo_estimates(&Conductor::copper(roughness), r_in, &rec);
+                assert!(
+                    (sampled - uniform).abs() < 0.02,
+                    "{roughness}: {sampled} != {uniform}"
+                );
+                assert!(sampled <= 1.0);
+            }
+        }
+    }
+}
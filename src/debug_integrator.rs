@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use crate::{
+    hittable::{HitRecord, HitResult, Hittable},
+    integrator::Integrator,
+    ray::Ray,
+    sampler::SampleStream,
+    scene::Scene,
+    utils::clamp,
+    vec3::{Color, Vec3},
+};
+
+/// What the [`DebugIntegrator`] shows for the first surface hit by a camera ray.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugMode {
+    /// Shading normal, mapped from `[-1, 1]` to `[0, 1]`
+    ShadingNormal,
+    /// Outward normal of the geometry, mapped from `[-1, 1]` to `[0, 1]`
+    GeometricNormal,
+    /// Distance to the camera, white close by and black at the max distance
+    Depth,
+    /// Green for front faces, red for back faces
+    FrontFace,
+    /// `u` in red and `v` in green
+    Uv,
+    /// A random looking color per material
+    Material,
+    /// A random looking color per object
+    Object,
+}
+
+/// Shows properties of the geometry instead of lighting it, to track down modelling errors.
+///
+/// Missed rays stay black. The values still go through the gamma correction of the renderer.
+#[derive(Debug, Clone, Copy)]
+pub struct DebugIntegrator {
+    mode: DebugMode,
+    max_distance: f64,
+}
+
+impl DebugIntegrator {
+    pub fn new(mode: DebugMode) -> Self {
+        Self {
+            mode,
+            max_distance: 30.0,
+        }
+    }
+
+    /// Distance that is mapped to black in [`DebugMode::Depth`].
+    pub fn with_max_distance(mut self, max_distance: f64) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    pub fn mode(&self) -> DebugMode {
+        self.mode
+    }
+}
+
+impl Integrator for DebugIntegrator {
+    fn li(&self, r: Ray, scene: &Scene, _max_depth: usize, _samples: &mut SampleStream) -> Color {
+        let mut rec = HitRecord::default();
+        let mat = match scene.world().hit(r, 0.001, f64::INFINITY, &mut rec) {
+            HitResult::Hit(mat) => mat,
+            HitResult::Miss => return Vec3(0.0, 0.0, 0.0),
+        };
+
+        match self.mode {
+            DebugMode::ShadingNormal => 0.5 * (rec.normal + Vec3(1.0, 1.0, 1.0)),
+            DebugMode::GeometricNormal => 0.5 * (rec.geometric_normal + Vec3(1.0, 1.0, 1.0)),
+            DebugMode::Depth => {
+                let distance = rec.t * r.direction().length();
+                let depth = 1.0 - clamp(distance / self.max_distance, 0.0, 1.0);
+                Vec3(depth, depth, depth)
+            }
+            DebugMode::FrontFace => {
+                if rec.front_face() {
+                    Vec3(0.0, 1.0, 0.0)
+                } else {
+                    Vec3(1.0, 0.0, 0.0)
+                }
+            }
+            DebugMode::Uv => Vec3(rec.u, rec.v, 0.0),
+            DebugMode::Material => id_color(Arc::as_ptr(&mat) as *const () as usize),
+            DebugMode::Object => id_color(rec.object),
+        }
+    }
+}
+
+/// Derives a color from the address of a material or object, so every instance gets its own.
+fn id_color(id: usize) -> Color {
+    // splitmix64 finalizer to spread neighbouring addresses over the whole range
+    let mut x = id as u64;
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^= x >> 31;
+
+    let channel = |shift: u32| ((x >> shift) & 0xff) as f64 / 255.0;
+    Vec3(channel(0), channel(8), channel(16))
+}
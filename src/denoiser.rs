@@ -0,0 +1,188 @@
+use crate::{
+    aov::Aov,
+    error::Result,
+    framebuffer::Framebuffer,
+    vec3::{Color, Vec3},
+};
+
+/// Joint bilateral filter that smooths the noise of low sample renders.
+///
+/// Neighbouring pixels are only averaged when their albedo, normal and depth match, so
+/// edges and textures recorded in the [`Aov`]s stay sharp while the lighting is blurred.
+/// Needs an integrator that records AOVs, like the [`crate::PathTracer`].
+#[derive(Debug, Clone, Copy)]
+pub struct Denoiser {
+    radius: usize,
+    sigma_spatial: f64,
+    sigma_color: f64,
+    sigma_albedo: f64,
+    sigma_normal: f64,
+    sigma_depth: f64,
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self {
+            radius: 6,
+            sigma_spatial: 3.0,
+            sigma_color: 0.5,
+            sigma_albedo: 0.1,
+            sigma_normal: 0.3,
+            sigma_depth: 0.05,
+        }
+    }
+}
+
+impl Denoiser {
+    /// Pixels up to `radius` away in both directions are averaged.
+    pub fn with_radius(mut self, radius: usize) -> Self {
+        self.radius = radius;
+        self.sigma_spatial = (radius as f64 / 2.0).max(0.5);
+        self
+    }
+
+    /// Returns a copy of `framebuffer` with a filtered color, the AOVs are left as they are.
+    pub fn denoise(&self, framebuffer: &Framebuffer) -> Result<Framebuffer> {
+        let (width, height) = (framebuffer.width(), framebuffer.height());
+        let mut denoised = framebuffer.clone();
+        let guides: Vec<_> = (0..width * height)
+            .map(|idx| Guide::new(framebuffer, idx))
+            .collect();
+
+        let radius = self.radius as isize;
+        for y in 0..height {
+            for x in 0..width {
+                let idx = y * width + x;
+                let center = &guides[idx];
+
+                let mut sum = Vec3(0.0, 0.0, 0.0);
+                let mut weight_sum = 0.0;
+
+                for dy in -radius..=radius {
+                    for dx in -radius..=radius {
+                        let (qx, qy) = (x as isize + dx, y as isize + dy);
+                        if qx < 0 || qy < 0 || qx >= width as isize || qy >= height as isize {
+                            continue;
+                        }
+
+                        let q_idx = qy as usize * width + qx as usize;
+                        let weight = self.weight(center, &guides[q_idx], dx, dy);
+
+                        sum += weight * framebuffer.color(q_idx);
+                        weight_sum += weight;
+                    }
+                }
+
+                // the center always has a weight of one, so the sum is never zero
+                denoised.set_pixel(idx, sum / weight_sum, *framebuffer.aovs(idx))?;
+            }
+        }
+
+        Ok(denoised)
+    }
+
+    fn weight(&self, p: &Guide, q: &Guide, dx: isize, dy: isize) -> f64 {
+        let spatial = (dx * dx + dy * dy) as f64 / (self.sigma_spatial * self.sigma_spatial);
+        let color = (p.color - q.color).length_squared() / (self.sigma_color * self.sigma_color);
+        let albedo =
+            (p.albedo - q.albedo).length_squared() / (self.sigma_albedo * self.sigma_albedo);
+        let normal =
+            (p.normal - q.normal).length_squared() / (self.sigma_normal * self.sigma_normal);
+
+        // depth differences are relative, so far away surfaces get the same treatment
+        let depth_scale = self.sigma_depth * p.depth.max(q.depth).max(1e-3);
+        let depth = (p.depth - q.depth) * (p.depth - q.depth) / (depth_scale * depth_scale);
+
+        (-0.5 * (spatial + color + albedo + normal + depth)).exp()
+    }
+}
+
+/// Features of a pixel that decide whether neighbours belong to the same surface.
+struct Guide {
+    /// Roughly tone mapped, so bright outliers don't dominate the color distance
+    color: Color,
+    albedo: Color,
+    normal: Vec3,
+    depth: f64,
+}
+
+impl Guide {
+    fn new(framebuffer: &Framebuffer, idx: usize) -> Self {
+        let c = framebuffer.color(idx);
+        let aovs = framebuffer.aovs(idx);
+
+        Self {
+            color: Vec3(c.x().sqrt(), c.y().sqrt(), c.z().sqrt()),
+            albedo: aovs.get(Aov::Albedo),
+            normal: aovs.get(Aov::Normal),
+            depth: aovs.get(Aov::Depth).x(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aov::Aovs;
+
+    const SIZE: usize = 16;
+
+    /// Image whose left and right halves have their own color and first hit features.
+    fn halves(left: (Color, Aovs), right: (Color, Aovs)) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+        for idx in 0..SIZE * SIZE {
+            let (color, aovs) = if idx % SIZE < SIZE / 2 { left } else { right };
+            framebuffer.set_pixel(idx, color, aovs).unwrap();
+        }
+        framebuffer
+    }
+
+    fn aovs(albedo: Color, normal: Vec3) -> Aovs {
+        let mut aovs = Aovs::default();
+        aovs.set(Aov::Albedo, albedo);
+        aovs.set(Aov::Normal, normal);
+        aovs.set_depth(2.0);
+        aovs
+    }
+
+    fn max_change(before: &Framebuffer, after: &Framebuffer) -> f64 {
+        (0..SIZE * SIZE)
+            .map(|idx| (after.color(idx) - before.color(idx)).length())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn constant_image_is_unchanged() {
+        let pixel = (
+            Vec3(0.3, 0.5, 0.7),
+            aovs(Vec3(0.8, 0.8, 0.8), Vec3(0.0, 0.0, 1.0)),
+        );
+        let image = halves(pixel, pixel);
+        let denoised = Denoiser::default().denoise(&image).unwrap();
+        assert!(max_change(&image, &denoised) < 1e-12);
+    }
+
+    #[test]
+    fn edges_stay_sharp() {
+        let up = Vec3(0.0, 0.0, 1.0);
+        let grey = Vec3(0.8, 0.8, 0.8);
+        let edges = [
+            (aovs(Vec3(0.8, 0.1, 0.1), up), aovs(Vec3(0.1, 0.1, 0.8), up)),
+            (aovs(grey, up), aovs(grey, Vec3(1.0, 0.0, 0.0))),
+        ];
+
+        // colors close enough that the color alone wouldn't keep the halves apart
+        let (dark, bright) = (Vec3(0.5, 0.5, 0.5), Vec3(0.6, 0.6, 0.6));
+        for (left, right) in edges {
+            let image = halves((dark, left), (bright, right));
+            let denoised = Denoiser::default().denoise(&image).unwrap();
+            assert!(max_change(&image, &denoised) < 1e-3);
+        }
+
+        // without the features telling the halves apart the edge gets blurred
+        let same = aovs(grey, up);
+        let image = halves((dark, same), (bright, same));
+        let denoised = Denoiser::default().denoise(&image).unwrap();
+        assert!(max_change(&image, &denoised) > 0.01);
+    }
+}
@@ -1,20 +1,43 @@
-use rand::thread_rng;
-
 use crate::{
     hittable::HitRecord,
-    material::Material,
+    material::{Material, ScatterRecord},
     ray::Ray,
+    sampler::SampleStream,
     vec3::{Color, Vec3},
 };
 
+/// Smooth glass or liquid, clear unless it absorbs some of the light passing through it.
+///
+/// Absorption is applied when a ray leaves through the back face of the material, over the
+/// distance back to where it entered. Anything inside, e.g. an object submerged in a liquid,
+/// cuts the ray short, so the light reaching it through the liquid isn't absorbed.
 #[derive(Debug, Clone, Copy)]
 pub struct Dielectric {
-    ir: f64, // index of refraction
+    ir: f64,           // index of refraction
+    absorption: Color, // per unit of distance traveled inside
 }
 
 impl Dielectric {
     pub fn new(ir: f64) -> Self {
-        Self { ir }
+        Self {
+            ir,
+            absorption: Color::default(),
+        }
+    }
+
+    /// Light traveling a distance `d` inside keeps `exp(-absorption * d)` of its energy,
+    /// so thick parts look darker than thin ones.
+    pub fn with_absorption(mut self, absorption: Color) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// Absorbs light so that white light turns into `color` after traveling `distance`
+    /// inside, which is easier to pick than an absorption coefficient. `distance` has to be
+    /// positive, smaller values are raised to a tiny one.
+    pub fn with_color_at_distance(mut self, color: Color, distance: f64) -> Self {
+        self.absorption = absorption_for_color(color, distance);
+        self
     }
 }
 
@@ -23,10 +46,8 @@ impl Material for Dielectric {
         &self,
         r_in: Ray,
         rec: &HitRecord,
-        attenuation: &mut Color,
-        scattered: &mut Ray,
-    ) -> bool {
-        *attenuation = Vec3(1.0, 1.0, 1.0);
+        samples: &mut SampleStream,
+    ) -> Option<ScatterRecord> {
         let refraction_ratio = if rec.front_face() {
             1.0 / self.ir
         } else {
@@ -40,20 +61,48 @@ impl Material for Dielectric {
 
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
 
-        let mut rng = thread_rng();
+        let direction =
+            if cannot_refract || reflectance(cos_theta, refraction_ratio) > samples.get_1d() {
+                Vec3::reflect(unit_direction, rec.normal)
+            } else {
+                Vec3::refract(unit_direction, rec.normal, refraction_ratio)
+            };
 
-        let direction = if cannot_refract
-            || reflectance(cos_theta, refraction_ratio) > rand::Rng::gen(&mut rng)
-        {
-            Vec3::reflect(unit_direction, rec.normal)
-        } else {
-            Vec3::refract(unit_direction, rec.normal, refraction_ratio)
-        };
+        Some(ScatterRecord {
+            direction: Vec3::unit_vector(direction),
+            bsdf: transmittance(self.absorption, r_in, rec),
+            pdf: 1.0,
+            specular: true,
+        })
+    }
+}
 
-        *scattered = Ray::new(rec.p, direction, r_in.time());
+/// Absorption coefficient that turns white into `color` over `distance`.
+pub(crate) fn absorption_for_color(color: Color, distance: f64) -> Color {
+    // fully saturated channels would need an infinite coefficient, and no distance at all
+    // would divide white channels into NaN
+    let distance = distance.max(1e-6);
+    let coefficient = |c: f64| -c.clamp(1e-6, 1.0).ln() / distance;
+    Vec3(
+        coefficient(color.x()),
+        coefficient(color.y()),
+        coefficient(color.z()),
+    )
+}
 
-        true
+/// Part of the light that is left after traveling along `r_in` to `rec`, which is only
+/// absorbed if the ray traveled inside the medium.
+pub(crate) fn transmittance(absorption: Color, r_in: Ray, rec: &HitRecord) -> Color {
+    if rec.front_face() {
+        return Vec3(1.0, 1.0, 1.0);
     }
+
+    let distance = rec.t * r_in.direction().length();
+    Vec3(
+        (-absorption.x() * distance).exp(),
+        (-absorption.y() * distance).exp(),
+        (-absorption.z() * distance).exp(),
+    )
 }
 
 fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
@@ -62,3 +111,18 @@ fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
     r0 = r0 * r0;
     r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn absorption_for_color_without_distance() {
+        for distance in [0.0, -1.0] {
+            let absorption = absorption_for_color(Vec3(1.0, 0.5, 0.0), distance);
+            assert_eq!(absorption.x(), 0.0);
+            assert!(absorption.y().is_finite() && absorption.y() > 0.0);
+            assert!(absorption.z().is_finite() && absorption.z() > 0.0);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{
+    hittable::HitRecord,
+    material::{Material, ScatterRecord},
+    ray::Ray,
+    sampler::SampleStream,
+    vec3::Color,
+};
+
+/// Emits light from the front side of a surface and doesn't scatter any.
+pub struct DiffuseLight {
+    emit: Color,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        Self { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(
+        &self,
+        _r_in: Ray,
+        _rec: &HitRecord,
+        _samples: &mut SampleStream,
+    ) -> Option<ScatterRecord> {
+        None
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        if rec.front_face() {
+            self.emit
+        } else {
+            Color::default()
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{
+    light::{cone_pdf, sample_in_cone, Light, LightSample},
+    sampler::SampleStream,
+    vec3::{Color, Point3, Vec3},
+};
+
+/// Light from infinitely far away, like the sun.
+///
+/// With an angular diameter of zero all light arrives from exactly one direction and shadows
+/// are perfectly sharp, otherwise the light covers a disc on the sky and casts soft shadows.
+pub struct DirectionalLight {
+    to_light: Vec3,
+    irradiance: Color,
+    cos_theta_max: f64,
+}
+
+impl DirectionalLight {
+    /// `direction` is the direction the light travels in, `irradiance` the light arriving on a
+    /// surface facing the light and `angular_diameter` the size of the light disc in degrees.
+    pub fn new(direction: Vec3, irradiance: Color, angular_diameter: f64) -> Self {
+        Self {
+            to_light: -Vec3::unit_vector(direction),
+            irradiance,
+            cos_theta_max: (angular_diameter / 2.0).to_radians().cos(),
+        }
+    }
+
+    /// Radiance of the light disc, spreading the irradiance over its solid angle.
+    fn disc_radiance(&self) -> Color {
+        self.irradiance * cone_pdf(self.cos_theta_max)
+    }
+
+    fn disc_contains(&self, direction: Vec3) -> bool {
+        !self.is_delta()
+            && Vec3::dot(Vec3::unit_vector(direction), self.to_light) >= self.cos_theta_max
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _p: Point3, samples: &mut SampleStream) -> Option<LightSample> {
+        if self.is_delta() {
+            return Some(LightSample {
+                direction: self.to_light,
+                distance: f64::INFINITY,
+                radiance: self.irradiance,
+                pdf: 1.0,
+            });
+        }
+
+        Some(LightSample {
+            direction: sample_in_cone(self.to_light, self.cos_theta_max, samples.get_2d()),
+            distance: f64::INFINITY,
+            radiance: self.disc_radiance(),
+            pdf: cone_pdf(self.cos_theta_max),
+        })
+    }
+
+    fn is_delta(&self) -> bool {
+        self.cos_theta_max >= 1.0
+    }
+
+    fn pdf(&self, _p: Point3, direction: Vec3) -> f64 {
+        if !self.disc_contains(direction) {
+            return 0.0;
+        }
+
+        cone_pdf(self.cos_theta_max)
+    }
+
+    fn radiance(&self, direction: Vec3) -> Color {
+        if !self.disc_contains(direction) {
+            return Color::default();
+        }
+
+        self.disc_radiance()
+    }
+}
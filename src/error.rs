@@ -0,0 +1,56 @@
+use std::{fmt::Display, io, path::PathBuf};
+
+use crate::ppm_image::PpmImageType;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    File { path: PathBuf, source: io::Error },
+    UnsupportedImageType(PpmImageType),
+    InvalidImageSize { width: usize, height: usize },
+    PixelOutOfRange { idx: usize, len: usize },
+    WorkerPanicked,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "i/o error: {}", e),
+            Error::File { path, source } => {
+                write!(f, "could not write '{}': {}", path.display(), source)
+            }
+            Error::UnsupportedImageType(image_type) => {
+                write!(f, "image type {:?} is not supported", image_type)
+            }
+            Error::InvalidImageSize { width, height } => write!(
+                f,
+                "invalid image size {}x{}, both sides need at least 2 pixels",
+                width, height
+            ),
+            Error::PixelOutOfRange { idx, len } => write!(
+                f,
+                "pixel index {} out of range for image with {} pixels",
+                idx, len
+            ),
+            Error::WorkerPanicked => write!(f, "a render worker panicked"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::File { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
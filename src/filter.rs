@@ -0,0 +1,171 @@
+use std::f64::consts::PI;
+
+/// Shape of a [`Filter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    /// Every sample counts fully for the pixels within the radius
+    Box,
+    /// Weight falls off linearly towards the radius
+    Tent,
+    /// Gaussian with a standard deviation of a third of the radius
+    Gaussian,
+    /// Mitchell-Netravali cubic with `B = C = 1/3`, slightly sharpening
+    MitchellNetravali,
+    /// Sinc windowed by a wider sinc, sharp but rings at hard edges
+    Lanczos,
+}
+
+impl FilterKind {
+    fn default_radius(&self) -> f64 {
+        match self {
+            FilterKind::Box => 0.5,
+            FilterKind::Tent => 1.0,
+            FilterKind::Gaussian => 1.5,
+            FilterKind::MitchellNetravali => 2.0,
+            FilterKind::Lanczos => 2.0,
+        }
+    }
+}
+
+/// Reconstruction filter that weights how much a sample counts for the pixels around it.
+///
+/// Every sample is splatted into all pixels whose center is within the radius, and every
+/// pixel is the weighted average of the samples it received. The filter is separable, the
+/// weight is the product of the weights of both offsets. The default box filter with a
+/// radius of half a pixel keeps every sample in its own pixel.
+#[derive(Debug, Clone, Copy)]
+pub struct Filter {
+    kind: FilterKind,
+    radius: f64,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self::new(FilterKind::Box)
+    }
+}
+
+impl Filter {
+    /// Filter of the given kind with its usual radius.
+    pub fn new(kind: FilterKind) -> Self {
+        Self {
+            kind,
+            radius: kind.default_radius(),
+        }
+    }
+
+    /// Radius in pixels, the shape of the filter is stretched to cover it.
+    pub fn with_radius(mut self, radius: f64) -> Self {
+        self.radius = radius.max(0.5);
+        self
+    }
+
+    pub fn kind(&self) -> FilterKind {
+        self.kind
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    /// Weight of a sample `dx` and `dy` pixels away from the center of a pixel, can be
+    /// negative for the sharpening filters.
+    pub fn evaluate(&self, dx: f64, dy: f64) -> f64 {
+        self.evaluate_1d(dx) * self.evaluate_1d(dy)
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        let x = x.abs();
+        if x > self.radius {
+            return 0.0;
+        }
+
+        match self.kind {
+            FilterKind::Box => 1.0,
+            FilterKind::Tent => self.radius - x,
+            FilterKind::Gaussian => {
+                // shifted down so the weight reaches zero at the radius
+                let sigma = self.radius / 3.0;
+                let gaussian = |x: f64| (-x * x / (2.0 * sigma * sigma)).exp();
+                gaussian(x) - gaussian(self.radius)
+            }
+            FilterKind::MitchellNetravali => mitchell_netravali(2.0 * x / self.radius),
+            FilterKind::Lanczos => sinc(x) * sinc(x / self.radius),
+        }
+    }
+}
+
+/// Mitchell-Netravali cubic with `B = C = 1/3` on `[0, 2]`.
+fn mitchell_netravali(x: f64) -> f64 {
+    const B: f64 = 1.0 / 3.0;
+    const C: f64 = 1.0 / 3.0;
+
+    let value = if x < 1.0 {
+        (12.0 - 9.0 * B - 6.0 * C) * x * x * x
+            + (-18.0 + 12.0 * B + 6.0 * C) * x * x
+            + (6.0 - 2.0 * B)
+    } else {
+        (-B - 6.0 * C) * x * x * x
+            + (6.0 * B + 30.0 * C) * x * x
+            + (-12.0 * B - 48.0 * C) * x
+            + (8.0 * B + 24.0 * C)
+    };
+    value / 6.0
+}
+
+fn sinc(x: f64) -> f64 {
+    if x < 1e-5 {
+        return 1.0;
+    }
+    (PI * x).sin() / (PI * x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KINDS: [FilterKind; 5] = [
+        FilterKind::Box,
+        FilterKind::Tent,
+        FilterKind::Gaussian,
+        FilterKind::MitchellNetravali,
+        FilterKind::Lanczos,
+    ];
+
+    fn filters() -> impl Iterator<Item = Filter> {
+        KINDS
+            .into_iter()
+            .flat_map(|kind| [Filter::new(kind), Filter::new(kind).with_radius(3.0)])
+    }
+
+    #[test]
+    fn filters_are_symmetric() {
+        for filter in filters() {
+            for (dx, dy) in [(0.3, 0.0), (0.25, 0.7), (1.1, 0.4), (1.9, 2.6)] {
+                let weight = filter.evaluate(dx, dy);
+                assert_eq!(filter.evaluate(-dx, dy), weight, "{filter:?}");
+                assert_eq!(filter.evaluate(dx, -dy), weight, "{filter:?}");
+                assert_eq!(filter.evaluate(dy, dx), weight, "{filter:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn filters_vanish_beyond_their_radius() {
+        for filter in filters() {
+            let beyond = filter.radius() + 1e-9;
+            for offset in [0.0, 0.2, filter.radius()] {
+                assert_eq!(filter.evaluate(beyond, offset), 0.0, "{filter:?}");
+                assert_eq!(filter.evaluate(offset, -beyond), 0.0, "{filter:?}");
+                assert_eq!(filter.evaluate(2.0 * beyond, offset), 0.0, "{filter:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn filters_are_positive_at_the_center() {
+        for filter in filters() {
+            assert!(filter.evaluate(0.0, 0.0) > 0.0, "{filter:?}");
+        }
+    }
+}
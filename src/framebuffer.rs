@@ -0,0 +1,281 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    aov::{Aov, Aovs},
+    error::{Error, Result},
+    vec3::Color,
+};
+
+/// Linear, unclamped pixel values of a render together with its [`Aov`]s.
+///
+/// Pixels are stored top to bottom like in [`crate::PpmImage`].
+#[derive(Debug, Clone)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    colors: Vec<Color>,
+    aovs: Vec<Aovs>,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            colors: vec![Color::default(); width * height],
+            aovs: vec![Aovs::default(); width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn color(&self, idx: usize) -> Color {
+        self.colors[idx]
+    }
+
+    pub fn aovs(&self, idx: usize) -> &Aovs {
+        &self.aovs[idx]
+    }
+
+    pub fn set_pixel(&mut self, idx: usize, color: Color, aovs: Aovs) -> Result<()> {
+        if idx >= self.colors.len() {
+            return Err(Error::PixelOutOfRange {
+                idx,
+                len: self.colors.len(),
+            });
+        }
+
+        self.colors[idx] = color;
+        self.aovs[idx] = aovs;
+        Ok(())
+    }
+
+    /// Writes the color and all AOVs as layers of one uncompressed OpenEXR file.
+    pub fn save_exr(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let to_error = |source| Error::File {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        let file = File::create(path).map_err(to_error)?;
+        self.write_exr(BufWriter::new(file)).map_err(|e| match e {
+            Error::Io(source) => to_error(source),
+            e => e,
+        })
+    }
+
+    pub fn write_exr(&self, mut out: impl Write) -> Result<()> {
+        let channels = self.exr_channels();
+        let (width, height) = (self.width as i32, self.height as i32);
+
+        // magic number and version 2, single part scan line file
+        let mut header = vec![0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0];
+
+        let mut channel_list = Vec::new();
+        for (name, _) in &channels {
+            channel_list.extend_from_slice(name.as_bytes());
+            channel_list.push(0);
+            channel_list.extend_from_slice(&EXR_FLOAT.to_le_bytes());
+            channel_list.extend_from_slice(&[0, 0, 0, 0]); // linear flag and reserved
+            channel_list.extend_from_slice(&1i32.to_le_bytes()); // x sampling
+            channel_list.extend_from_slice(&1i32.to_le_bytes()); // y sampling
+        }
+        channel_list.push(0);
+
+        let window = [0, 0, width - 1, height - 1]
+            .iter()
+            .flat_map(|v: &i32| v.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        push_exr_attribute(&mut header, "channels", "chlist", &channel_list);
+        push_exr_attribute(&mut header, "compression", "compression", &[0]);
+        push_exr_attribute(&mut header, "dataWindow", "box2i", &window);
+        push_exr_attribute(&mut header, "displayWindow", "box2i", &window);
+        push_exr_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+        push_exr_attribute(
+            &mut header,
+            "pixelAspectRatio",
+            "float",
+            &1f32.to_le_bytes(),
+        );
+        push_exr_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+        push_exr_attribute(
+            &mut header,
+            "screenWindowWidth",
+            "float",
+            &1f32.to_le_bytes(),
+        );
+        header.push(0);
+        out.write_all(&header)?;
+
+        // every line is its own chunk, the offset table points at them
+        let header_size = header.len();
+        let line_size = channels.len() * self.width * 4;
+        let chunk_size = 8 + line_size;
+        let table_size = 8 * self.height;
+        for y in 0..self.height {
+            let offset = (header_size + table_size + y * chunk_size) as u64;
+            out.write_all(&offset.to_le_bytes())?;
+        }
+
+        for y in 0..self.height {
+            out.write_all(&(y as i32).to_le_bytes())?;
+            out.write_all(&(line_size as i32).to_le_bytes())?;
+
+            for (_, value) in &channels {
+                for x in 0..self.width {
+                    let v = value(self, y * self.width + x) as f32;
+                    out.write_all(&v.to_le_bytes())?;
+                }
+            }
+        }
+
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Channels in the alphabetical order required by the file format, with a way to read them.
+    #[allow(clippy::type_complexity)]
+    fn exr_channels(&self) -> Vec<(String, Box<dyn Fn(&Framebuffer, usize) -> f64>)> {
+        let mut channels: Vec<(String, Box<dyn Fn(&Framebuffer, usize) -> f64>)> = vec![
+            ("R".to_string(), Box::new(|fb, idx| fb.colors[idx].x())),
+            ("G".to_string(), Box::new(|fb, idx| fb.colors[idx].y())),
+            ("B".to_string(), Box::new(|fb, idx| fb.colors[idx].z())),
+        ];
+
+        for aov in Aov::ALL {
+            for (i, channel) in aov.channels().iter().enumerate() {
+                let component = move |c: Color| match i {
+                    0 => c.x(),
+                    1 => c.y(),
+                    _ => c.z(),
+                };
+                channels.push((
+                    format!("{}.{}", aov.name(), channel),
+                    Box::new(move |fb, idx| component(fb.aovs[idx].get(aov))),
+                ));
+            }
+        }
+
+        channels.sort_by(|a, b| a.0.cmp(&b.0));
+        channels
+    }
+}
+
+/// Pixel type of 32 bit floats
+const EXR_FLOAT: i32 = 2;
+
+fn push_exr_attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Vec3;
+
+    fn read_i32(bytes: &[u8], at: usize) -> i32 {
+        i32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn read_string(bytes: &[u8], at: usize) -> (String, usize) {
+        let end = at + bytes[at..].iter().position(|&b| b == 0).unwrap();
+        (String::from_utf8(bytes[at..end].to_vec()).unwrap(), end + 1)
+    }
+
+    /// Returns the attributes by name and the position right after the header.
+    fn read_header(bytes: &[u8]) -> (Vec<(String, Vec<u8>)>, usize) {
+        let mut attributes = Vec::new();
+        let mut at = 8;
+        loop {
+            let (name, next) = read_string(bytes, at);
+            if name.is_empty() {
+                return (attributes, next);
+            }
+            let (_, next) = read_string(bytes, next);
+            let size = read_i32(bytes, next) as usize;
+            attributes.push((name, bytes[next + 4..next + 4 + size].to_vec()));
+            at = next + 4 + size;
+        }
+    }
+
+    fn channel_names(channel_list: &[u8]) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut at = 0;
+        loop {
+            let (name, next) = read_string(channel_list, at);
+            if name.is_empty() {
+                return names;
+            }
+            assert_eq!(read_i32(channel_list, next), EXR_FLOAT);
+            names.push(name);
+            at = next + 16;
+        }
+    }
+
+    #[test]
+    fn write_exr_layout() {
+        let (width, height) = (3, 2);
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer
+            .set_pixel(4, Vec3(0.25, 0.5, 2.0), Aovs::default())
+            .unwrap();
+
+        let mut bytes = Vec::new();
+        framebuffer.write_exr(&mut bytes).unwrap();
+
+        assert_eq!(bytes[..4], [0x76, 0x2f, 0x31, 0x01]);
+        assert_eq!(bytes[4], 2);
+
+        let (attributes, header_end) = read_header(&bytes);
+        let channel_list = &attributes
+            .iter()
+            .find(|(name, _)| name == "channels")
+            .unwrap()
+            .1;
+        let channels = channel_names(channel_list);
+        let mut sorted = channels.clone();
+        sorted.sort();
+        assert_eq!(channels, sorted);
+        assert!(channels.contains(&"R".to_string()));
+
+        // one chunk per line, right after the offset table and each other
+        let line_size = channels.len() * width * 4;
+        let offsets: Vec<usize> = (0..height)
+            .map(|y| {
+                let at = header_end + 8 * y;
+                u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap()) as usize
+            })
+            .collect();
+        assert_eq!(offsets[0], header_end + 8 * height);
+        for (y, &offset) in offsets.iter().enumerate() {
+            assert_eq!(read_i32(&bytes, offset), y as i32);
+            assert_eq!(read_i32(&bytes, offset + 4), line_size as i32);
+        }
+        assert_eq!(offsets[height - 1] + 8 + line_size, bytes.len());
+
+        // pixel 4 is the second one of the second line
+        let b = channels.iter().position(|name| name == "B").unwrap();
+        let at = offsets[1] + 8 + (b * width + 1) * 4;
+        assert_eq!(
+            f32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()),
+            2.0
+        );
+    }
+}
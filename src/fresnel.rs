@@ -0,0 +1,92 @@
+use crate::vec3::{Color, Vec3};
+
+/// Fraction of light reflected by a conductor with the complex index of refraction
+/// `eta + i k` per color channel, for light arriving at `cos_theta` to the normal.
+pub fn conductor(cos_theta: f64, eta: Color, k: Color) -> Color {
+    Vec3(
+        conductor_channel(cos_theta, eta.x(), k.x()),
+        conductor_channel(cos_theta, eta.y(), k.y()),
+        conductor_channel(cos_theta, eta.z(), k.z()),
+    )
+}
+
+fn conductor_channel(cos_theta: f64, eta: f64, k: f64) -> f64 {
+    let cos2 = cos_theta.clamp(0.0, 1.0).powi(2);
+    let sin2 = 1.0 - cos2;
+    let (eta2, k2) = (eta * eta, k * k);
+
+    let t0 = eta2 - k2 - sin2;
+    let a2_plus_b2 = (t0 * t0 + 4.0 * eta2 * k2).sqrt();
+    let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+
+    let t1 = a2_plus_b2 + cos2;
+    let t2 = 2.0 * cos2.sqrt() * a;
+    let rs = (t1 - t2) / (t1 + t2);
+
+    let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+    let t4 = t2 * sin2;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+
+    0.5 * (rs + rp)
+}
+
+/// Fraction of unpolarized light reflected at the boundary to a dielectric, for light
+/// arriving at `cos_theta` to the normal and `eta` being the ratio of the indices of
+/// refraction behind and in front of the boundary.
+pub fn dielectric(cos_theta: f64, eta: f64) -> f64 {
+    let cos_i = cos_theta.clamp(0.0, 1.0);
+    let sin2_t = (1.0 - cos_i * cos_i) / (eta * eta);
+    if sin2_t >= 1.0 {
+        // total internal reflection
+        return 1.0;
+    }
+
+    let cos_t = (1.0 - sin2_t).sqrt();
+    let rs = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    let rp = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    0.5 * (rs * rs + rp * rp)
+}
+
+/// Schlick's approximation of the reflectance for a surface that reflects `f0` of the light
+/// arriving along the normal.
+pub fn schlick(cos_theta: f64, f0: Color) -> Color {
+    let weight = (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5);
+    f0 + weight * (Vec3(1.0, 1.0, 1.0) - f0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dielectric_at_normal_incidence() {
+        assert!((dielectric(1.0, 1.5) - 0.04).abs() < 1e-12);
+        assert!((dielectric(1.0, 1.0 / 1.5) - 0.04).abs() < 1e-12);
+    }
+
+    #[test]
+    fn dielectric_total_internal_reflection() {
+        // the critical angle of glass seen from the inside is about 41.8 degrees
+        assert_eq!(dielectric(0.5, 1.0 / 1.5), 1.0);
+        assert!(dielectric(0.8, 1.0 / 1.5) < 1.0);
+    }
+
+    #[test]
+    fn conductor_at_normal_incidence() {
+        let (eta, k) = (Vec3(0.143, 0.374, 1.442), Vec3(3.983, 2.385, 1.603));
+        let expected = |n: f64, k: f64| ((n - 1.0).powi(2) + k * k) / ((n + 1.0).powi(2) + k * k);
+
+        let f = conductor(1.0, eta, k);
+        assert!((f.x() - expected(eta.x(), k.x())).abs() < 1e-12);
+        assert!((f.y() - expected(eta.y(), k.y())).abs() < 1e-12);
+        assert!((f.z() - expected(eta.z(), k.z())).abs() < 1e-12);
+    }
+
+    #[test]
+    fn conductor_without_absorption_is_a_dielectric() {
+        for cos_theta in [0.1, 0.5, 0.9] {
+            let f = conductor(cos_theta, Vec3(1.5, 1.5, 1.5), Vec3(0.0, 0.0, 0.0));
+            assert!((f.x() - dielectric(cos_theta, 1.5)).abs() < 1e-9);
+        }
+    }
+}
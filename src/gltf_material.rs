@@ -0,0 +1,70 @@
+use crate::vec3::{Color, Vec3};
+
+/// Parameters of a glTF 2.0 metallic-roughness material, as found in the `materials` of a
+/// glTF file, to be turned into a [`crate::Principled`] material.
+///
+/// The defaults are the ones of the specification. Textures are not supported, only the
+/// factors are. Besides the core material the `KHR_materials_ior`, `KHR_materials_specular`,
+/// `KHR_materials_transmission`, `KHR_materials_clearcoat` and `KHR_materials_sheen`
+/// extensions are understood. Of `KHR_materials_volume` only the thickness is, to tell
+/// solid from thin-walled transmission, the attenuation of the volume is ignored.
+#[derive(Debug, Clone, Copy)]
+pub struct GltfMaterial {
+    /// Linear RGBA, the alpha channel is ignored
+    pub base_color_factor: [f64; 4],
+    pub metallic_factor: f64,
+    pub roughness_factor: f64,
+    pub emissive_factor: [f64; 3],
+    pub ior: f64,
+    pub specular_factor: f64,
+    pub specular_color_factor: [f64; 3],
+    pub transmission_factor: f64,
+    /// Zero makes transmissive materials thin-walled
+    pub thickness_factor: f64,
+    pub clearcoat_factor: f64,
+    pub clearcoat_roughness_factor: f64,
+    pub sheen_color_factor: [f64; 3],
+    pub sheen_roughness_factor: f64,
+}
+
+impl Default for GltfMaterial {
+    fn default() -> Self {
+        Self {
+            base_color_factor: [1.0, 1.0, 1.0, 1.0],
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            emissive_factor: [0.0, 0.0, 0.0],
+            ior: 1.5,
+            specular_factor: 1.0,
+            specular_color_factor: [1.0, 1.0, 1.0],
+            transmission_factor: 0.0,
+            thickness_factor: 0.0,
+            clearcoat_factor: 0.0,
+            clearcoat_roughness_factor: 0.0,
+            sheen_color_factor: [0.0, 0.0, 0.0],
+            sheen_roughness_factor: 0.0,
+        }
+    }
+}
+
+impl GltfMaterial {
+    pub fn base_color(&self) -> Color {
+        let [r, g, b, _] = self.base_color_factor;
+        Vec3(r, g, b)
+    }
+
+    pub fn emission(&self) -> Color {
+        let [r, g, b] = self.emissive_factor;
+        Vec3(r, g, b)
+    }
+
+    pub fn specular_color(&self) -> Color {
+        let [r, g, b] = self.specular_color_factor;
+        Vec3(r, g, b)
+    }
+
+    pub fn sheen_color(&self) -> Color {
+        let [r, g, b] = self.sheen_color_factor;
+        Vec3(r, g, b)
+    }
+}
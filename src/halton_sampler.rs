@@ -0,0 +1,74 @@
+use crate::sampler::{mix, to_unit, Sampler};
+
+/// Bases of the first dimensions, beyond them the sampler falls back to random numbers
+const PRIMES: [u64; 32] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131,
+];
+
+/// Halton sequence with one prime base per dimension.
+///
+/// Every pixel shifts the sequence by its own random offset (Cranley-Patterson rotation),
+/// so neighbouring pixels don't repeat the same pattern.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HaltonSampler;
+
+impl Sampler for HaltonSampler {
+    fn get_1d(&self, seed: u64, index: usize, _count: usize, dimension: usize) -> f64 {
+        if dimension >= PRIMES.len() {
+            return to_unit(mix(mix(seed, index as u64), dimension as u64));
+        }
+
+        let offset = to_unit(mix(seed, dimension as u64));
+        let value = radical_inverse(PRIMES[dimension], index as u64) + offset;
+        value - value.floor()
+    }
+}
+
+/// Mirrors the digits of `index` in `base` at the decimal point.
+fn radical_inverse(base: u64, mut index: u64) -> f64 {
+    let inv_base = 1.0 / base as f64;
+    let mut inv_base_n = 1.0;
+    let mut reversed = 0;
+
+    while index > 0 {
+        let next = index / base;
+        reversed = reversed * base + (index - next * base);
+        inv_base_n *= inv_base;
+        index = next;
+    }
+
+    f64::min(reversed as f64 * inv_base_n, 1.0 - f64::EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampler::{checks, pixel_seed};
+
+    #[test]
+    fn every_stratum_gets_one_sample() {
+        for seed in [pixel_seed(0, 0), pixel_seed(17, 3)] {
+            // a prefix of a power of the base is stratified in that dimension
+            for k in 0..10 {
+                assert!(checks::covers_strata(&HaltonSampler, seed, 1 << k, 0));
+            }
+            for k in 0..6 {
+                assert!(checks::covers_strata(
+                    &HaltonSampler,
+                    seed,
+                    3usize.pow(k),
+                    1
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn radical_inverse_mirrors_digits() {
+        assert_eq!(radical_inverse(2, 0), 0.0);
+        assert_eq!(radical_inverse(2, 1), 0.5);
+        assert_eq!(radical_inverse(2, 6), 0.375);
+        assert!((radical_inverse(3, 5) - 7.0 / 9.0).abs() < 1e-12);
+    }
+}
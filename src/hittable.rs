@@ -3,20 +3,32 @@ use std::sync::Arc;
 use crate::{
     material::Material,
     ray::Ray,
+    sampler::SampleStream,
+    stats,
     vec3::{Point3, Vec3},
 };
 
 #[derive(Clone, Default)]
 pub struct HitRecord {
     pub p: Point3,
+    /// Shading normal, always facing against the incoming ray
     pub normal: Vec3,
+    /// Outward normal of the surface itself
+    pub geometric_normal: Vec3,
     pub t: f64,
+    /// Surface coordinates of the hit, both in `[0, 1]`
+    pub u: f64,
+    pub v: f64,
+    /// Tells the primitives apart, the address of the one that was hit
+    pub object: usize,
 
     front_face: bool,
 }
 
 impl HitRecord {
+    /// Sets the geometric normal and orients the shading normal against `r`.
     pub fn set_front_face(&mut self, r: Ray, outward_normal: Vec3) {
+        self.geometric_normal = outward_normal;
         self.front_face = Vec3::dot(r.direction(), outward_normal) < 0.0;
         self.normal = if self.front_face() {
             outward_normal
@@ -63,6 +75,20 @@ impl HitResult {
 
 pub trait Hittable: Send + Sync {
     fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> HitResult;
+
+    /// Picks a direction from `origin` towards a point on the object, so it can be used as an
+    /// area light.
+    ///
+    /// Objects that can't be sampled return `None`.
+    fn sample_direction(&self, _origin: Point3, _samples: &mut SampleStream) -> Option<Vec3> {
+        None
+    }
+
+    /// Density (per solid angle) of [`Hittable::sample_direction`] picking `direction`, zero
+    /// if a ray from `origin` into `direction` misses the object.
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3) -> f64 {
+        0.0
+    }
 }
 
 #[derive(Default)]
@@ -92,6 +118,8 @@ impl Hittable for HittableList {
         let mut last_hit_material: Option<Arc<dyn Material>>;
         last_hit_material = None;
 
+        stats::count_intersection_tests(self.objects.len());
+
         for obj in &self.objects {
             if let HitResult::Hit(mat) = obj.hit(r, t_min, closest_so_far, &mut temp_rec) {
                 hit_anything = true;
@@ -0,0 +1,11 @@
+use crate::sampler::{mix, to_unit, Sampler};
+
+/// Independent uniform random numbers, the baseline the other samplers improve on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IndependentSampler;
+
+impl Sampler for IndependentSampler {
+    fn get_1d(&self, seed: u64, index: usize, _count: usize, dimension: usize) -> f64 {
+        to_unit(mix(mix(seed, index as u64), dimension as u64))
+    }
+}
@@ -0,0 +1,24 @@
+use crate::{aov::Aovs, ray::Ray, sampler::SampleStream, scene::Scene, vec3::Color};
+
+/// Computes the light arriving along camera rays, the renderer averages it into pixels.
+pub trait Integrator: Send + Sync {
+    /// Light arriving at the origin of `r` from the scene, following at most `max_depth` bounces.
+    ///
+    /// The random numbers of the path are drawn from `samples`, starting every bounce with
+    /// [`SampleStream::start_bounce`].
+    fn li(&self, r: Ray, scene: &Scene, max_depth: usize, samples: &mut SampleStream) -> Color;
+
+    /// Like [`Integrator::li`], but also records the [`crate::Aov`]s of the path in `aovs`.
+    ///
+    /// Integrators that don't support AOVs leave them black.
+    fn li_with_aovs(
+        &self,
+        r: Ray,
+        scene: &Scene,
+        max_depth: usize,
+        _aovs: &mut Aovs,
+        samples: &mut SampleStream,
+    ) -> Color {
+        self.li(r, scene, max_depth, samples)
+    }
+}
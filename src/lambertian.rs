@@ -1,7 +1,11 @@
+use std::f64::consts::PI;
+
 use crate::{
     hittable::HitRecord,
-    material::Material,
+    material::{Material, ScatterRecord},
+    onb::Onb,
     ray::Ray,
+    sampler::SampleStream,
     vec3::{Color, Vec3},
 };
 
@@ -20,18 +24,43 @@ impl Material for Lambertian {
         &self,
         r_in: Ray,
         rec: &HitRecord,
-        attenuation: &mut Color,
-        scattered: &mut Ray,
-    ) -> bool {
-        let mut scatter_direction = rec.normal + Vec3::random_unit_vector();
-
-        // catch degenerate scatter direction
-        if scatter_direction.near_zero() {
-            scatter_direction = rec.normal;
+        samples: &mut SampleStream,
+    ) -> Option<ScatterRecord> {
+        let direction =
+            Onb::from_w(rec.normal).local(Vec3::sample_cosine_direction(samples.get_2d()));
+
+        // directions grazing the surface carry no light
+        let pdf = self.pdf(r_in, rec, direction);
+        if pdf <= 0.0 {
+            return None;
         }
 
-        *scattered = Ray::new(rec.p, scatter_direction, r_in.time());
-        *attenuation = self.albedo;
+        Some(ScatterRecord {
+            direction,
+            bsdf: self.bsdf(r_in, rec, direction),
+            pdf,
+            specular: false,
+        })
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+
+    fn is_diffuse(&self) -> bool {
         true
     }
+
+    fn bsdf(&self, _r_in: Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        if Vec3::dot(direction, rec.normal) <= 0.0 {
+            return Color::default();
+        }
+
+        self.albedo / PI
+    }
+
+    fn pdf(&self, _r_in: Ray, rec: &HitRecord, direction: Vec3) -> f64 {
+        let cosine = Vec3::dot(Vec3::unit_vector(direction), rec.normal);
+        f64::max(cosine, 0.0) / PI
+    }
 }
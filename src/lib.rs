@@ -0,0 +1,103 @@
+//! A small path tracer based on the "Ray Tracing in One Weekend" series.
+//!
+//! Build a [`Scene`] out of objects and [`Material`]s, point a [`Camera`] at it
+//! and hand both to a [`Renderer`] to get a [`PpmImage`] back.
+
+#![warn(clippy::correctness)]
+#![warn(clippy::suspicious)]
+#![warn(clippy::complexity)]
+#![warn(clippy::style)]
+#![warn(clippy::perf)]
+
+mod ambient_occlusion;
+mod aov;
+mod blue_noise_sampler;
+mod camera;
+mod cancellation;
+mod coated;
+mod conductor;
+mod debug_integrator;
+mod denoiser;
+mod dielectric;
+mod diffuse_light;
+mod directional_light;
+mod error;
+mod filter;
+mod framebuffer;
+mod fresnel;
+mod gltf_material;
+mod halton_sampler;
+mod hittable;
+mod independent_sampler;
+mod integrator;
+mod lambertian;
+mod light;
+mod material;
+mod metal;
+mod microfacet;
+mod moving_sphere;
+mod onb;
+mod path_tracer;
+mod point_light;
+mod ppm_image;
+mod principled;
+mod progress;
+mod quad;
+mod ray;
+mod renderer;
+mod rough_dielectric;
+mod sampler;
+mod scene;
+mod sky;
+mod sobol_sampler;
+mod sphere;
+mod spot_light;
+mod stats;
+mod stratified_sampler;
+mod utils;
+mod vec3;
+
+pub use ambient_occlusion::AmbientOcclusion;
+pub use aov::{Aov, Aovs};
+pub use blue_noise_sampler::BlueNoiseSampler;
+pub use camera::Camera;
+pub use cancellation::CancellationToken;
+pub use coated::Coated;
+pub use conductor::Conductor;
+pub use debug_integrator::{DebugIntegrator, DebugMode};
+pub use denoiser::Denoiser;
+pub use dielectric::Dielectric;
+pub use diffuse_light::DiffuseLight;
+pub use directional_light::DirectionalLight;
+pub use error::{Error, Result};
+pub use filter::{Filter, FilterKind};
+pub use framebuffer::Framebuffer;
+pub use gltf_material::GltfMaterial;
+pub use halton_sampler::HaltonSampler;
+pub use hittable::{HitRecord, HitResult, Hittable, HittableList};
+pub use independent_sampler::IndependentSampler;
+pub use integrator::Integrator;
+pub use lambertian::Lambertian;
+pub use light::{Falloff, Light, LightSample};
+pub use material::{Material, ScatterRecord};
+pub use metal::Metal;
+pub use moving_sphere::MovingSphere;
+pub use onb::Onb;
+pub use path_tracer::PathTracer;
+pub use point_light::PointLight;
+pub use ppm_image::{PpmImage, PpmImageType};
+pub use principled::Principled;
+pub use progress::{Progress, ProgressObserver, SilentProgress, TerminalProgress};
+pub use quad::Quad;
+pub use ray::Ray;
+pub use renderer::{RenderOutput, Renderer};
+pub use rough_dielectric::RoughDielectric;
+pub use sampler::{SampleStream, Sampler};
+pub use scene::{Background, Scene};
+pub use sky::PreethamSky;
+pub use sobol_sampler::SobolSampler;
+pub use sphere::Sphere;
+pub use spot_light::SpotLight;
+pub use stats::{RayCounters, RenderStats};
+pub use stratified_sampler::StratifiedSampler;
+pub use vec3::{Color, Point3, Vec3};
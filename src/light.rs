@@ -0,0 +1,81 @@
+use std::f64::consts::PI;
+
+use crate::{
+    onb::Onb,
+    sampler::SampleStream,
+    vec3::{Color, Point3, Vec3},
+};
+
+/// Direction towards a light, as seen from a shading point, see [`Light::sample`].
+#[derive(Debug, Clone, Copy)]
+pub struct LightSample {
+    pub direction: Vec3, // unit vector pointing at the light
+    pub distance: f64,   // infinite for lights that are infinitely far away
+    pub radiance: Color, // arriving at the shading point
+    pub pdf: f64,        // per solid angle, 1 for delta lights
+}
+
+/// Light source that is not part of the geometry and is only found by sampling it.
+pub trait Light: Send + Sync {
+    /// Picks a direction from `p` towards the light, or `None` if `p` doesn't receive any light.
+    fn sample(&self, p: Point3, samples: &mut SampleStream) -> Option<LightSample>;
+
+    /// Returns `true` if the light can only be reached by [`Light::sample`], because it
+    /// shines from a single point or direction.
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    /// Density of [`Light::sample`] picking `direction` at `p`, zero for delta lights.
+    fn pdf(&self, _p: Point3, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    /// Light arriving along a ray that leaves the scene into `direction`.
+    fn radiance(&self, _direction: Vec3) -> Color {
+        Color::default()
+    }
+}
+
+/// How the intensity of a positional light decreases with distance.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Falloff {
+    /// Physically correct, `1 / d^2`
+    #[default]
+    InverseSquare,
+    /// `1 / d`
+    Linear,
+    /// Constant intensity at any distance
+    None,
+}
+
+impl Falloff {
+    pub fn attenuation(&self, distance: f64) -> f64 {
+        match self {
+            Falloff::InverseSquare => 1.0 / (distance * distance),
+            Falloff::Linear => 1.0 / distance,
+            Falloff::None => 1.0,
+        }
+    }
+}
+
+/// Uniformly maps `u` from the unit square to a direction inside the cone around `axis` with
+/// the given cosine of its half angle.
+pub fn sample_in_cone(axis: Vec3, cos_theta_max: f64, u: (f64, f64)) -> Vec3 {
+    let (r1, r2) = u;
+
+    let cos_theta = 1.0 - r1 * (1.0 - cos_theta_max);
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = 2.0 * PI * r2;
+
+    Onb::from_w(axis).local(Vec3(
+        phi.cos() * sin_theta,
+        phi.sin() * sin_theta,
+        cos_theta,
+    ))
+}
+
+/// Density of [`sample_in_cone`], as long as the direction lies inside the cone.
+pub fn cone_pdf(cos_theta_max: f64) -> f64 {
+    1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+}
@@ -4,34 +4,81 @@
 #![warn(clippy::style)]
 #![warn(clippy::perf)]
 
-mod camera;
-mod dielectric;
-mod hittable;
-mod lambertian;
-mod material;
-mod metal;
-mod moving_sphere;
-mod ppm_image;
-mod ray;
-mod renderer;
-mod sphere;
-mod utils;
-mod vec3;
-
-use std::sync::Arc;
-
-use material::Material;
-
-use moving_sphere::MovingSphere;
-use rand::{thread_rng, Rng};
-use utils::random_in_range;
-use vec3::Vec3;
+use std::{process::ExitCode, sync::Arc, time::Instant};
 
-use crate::{
-    dielectric::Dielectric, hittable::HittableList, lambertian::Lambertian, metal::Metal,
-    ppm_image::PpmImage, renderer::Renderer, sphere::Sphere, vec3::Color,
+use rand::{thread_rng, Rng};
+use simple_raytracer::{
+    AmbientOcclusion, Background, BlueNoiseSampler, Camera, Color, Conductor, DebugIntegrator,
+    DebugMode, Denoiser, Dielectric, DiffuseLight, DirectionalLight, Filter, FilterKind,
+    HaltonSampler, HittableList, IndependentSampler, Integrator, Lambertian, Material, Metal,
+    MovingSphere, PathTracer, PointLight, PpmImage, PpmImageType, PreethamSky, Quad, Renderer,
+    Sampler, Scene, SobolSampler, Sphere, SpotLight, StratifiedSampler, TerminalProgress, Vec3,
 };
 
+const USAGE: &str = "usage: simple-raytracer [--scene random|lights|outdoor] [--integrator path|ao|debug-MODE] [--sampler independent|stratified|halton|sobol|blue-noise] [--filter box|tent|gaussian|mitchell|lanczos] [--filter-radius R] [--samples N] [--output FILE] [--denoise FILE] [--aovs FILE.exr]
+
+debug modes: normal, geometric-normal, depth, front-face, uv, material, object";
+
+struct Options {
+    scene: String,
+    integrator: String,
+    sampler: String,
+    filter: String,
+    filter_radius: Option<f64>,
+    samples_per_pixel: usize,
+    output: String,
+    denoised_output: Option<String>,
+    aovs: Option<String>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            scene: "random".to_string(),
+            integrator: "path".to_string(),
+            sampler: "independent".to_string(),
+            filter: "box".to_string(),
+            filter_radius: None,
+            samples_per_pixel: 100,
+            output: "image.ppm".to_string(),
+            denoised_output: None,
+            aovs: None,
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--scene" => options.scene = next_value(&mut args, &arg)?,
+                "--integrator" => options.integrator = next_value(&mut args, &arg)?,
+                "--sampler" => options.sampler = next_value(&mut args, &arg)?,
+                "--filter" => options.filter = next_value(&mut args, &arg)?,
+                "--filter-radius" => options.filter_radius = Some(parse_value(&mut args, &arg)?),
+                "--samples" => options.samples_per_pixel = parse_value(&mut args, &arg)?,
+                "--output" => options.output = next_value(&mut args, &arg)?,
+                "--denoise" => options.denoised_output = Some(next_value(&mut args, &arg)?),
+                "--aovs" => options.aovs = Some(next_value(&mut args, &arg)?),
+                _ => return Err(format!("unknown argument '{}'\n{}", arg, USAGE)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, arg: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("missing value for '{}'\n{}", arg, USAGE))
+}
+
+fn parse_value<T: std::str::FromStr>(
+    args: &mut impl Iterator<Item = String>,
+    arg: &str,
+) -> Result<T, String> {
+    let value = next_value(args, arg)?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for '{}'", value, arg))
+}
+
 fn random_scene() -> HittableList {
     let mut world = HittableList::default();
 
@@ -61,7 +108,7 @@ fn random_scene() -> HittableList {
                     sphere_material = Arc::new(Lambertian::new(albedo));
                     world.add(Arc::new(Sphere::new(center, 0.2, sphere_material.clone())));
 
-                    let center2 = center + Vec3(0.0, random_in_range(0.0, 0.5), 0.0);
+                    let center2 = center + Vec3(0.0, thread_rng().gen_range(0.0..0.5), 0.0);
                     world.add(Arc::new(MovingSphere::new(
                         center,
                         center2,
@@ -103,26 +150,252 @@ fn random_scene() -> HittableList {
     world
 }
 
-fn main() {
+fn random_scene_camera(aspect_ratio: f64) -> Camera {
+    let lookfrom = Vec3(13.0, 2.0, 3.0);
+    let lookat = Vec3(0.0, 0.0, 0.0);
+    let vup = Vec3(0.0, 1.0, 0.0);
+    let dist_to_focus = 10.0;
+    let aperture = 0.1;
+
+    Camera::new(
+        lookfrom,
+        lookat,
+        vup,
+        20.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    )
+}
+
+/// A few spheres on the ground at night, lit by every kind of light.
+fn lights_scene() -> Scene {
+    let mut scene = Scene::new(HittableList::default());
+    scene.set_background(Background::Solid(Vec3(0.01, 0.01, 0.02)));
+
+    let ground_material = Arc::new(Lambertian::new(Vec3(0.5, 0.5, 0.5)));
+    scene.add(Arc::new(Sphere::new(
+        Vec3(0.0, -1000.0, 0.0),
+        1000.0,
+        ground_material,
+    )));
+
+    scene.add(Arc::new(Sphere::new(
+        Vec3(-4.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::new(Vec3(0.4, 0.2, 0.1))),
+    )));
+    scene.add(Arc::new(Sphere::new(
+        Vec3(0.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+    scene.add(Arc::new(Sphere::new(
+        Vec3(4.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Metal::new(Vec3(0.7, 0.6, 0.5), 0.05)),
+    )));
+
+    scene.add_area_light(Arc::new(Sphere::new(
+        Vec3(-2.0, 3.0, 2.0),
+        0.25,
+        Arc::new(DiffuseLight::new(Vec3(40.0, 36.0, 30.0))),
+    )));
+    scene.add_area_light(Arc::new(Sphere::new(
+        Vec3(3.0, 2.5, -2.5),
+        0.15,
+        Arc::new(DiffuseLight::new(Vec3(10.0, 20.0, 60.0))),
+    )));
+
+    // panel above the glass sphere, facing down
+    scene.add_area_light(Arc::new(Quad::new(
+        Vec3(-1.5, 4.0, -3.5),
+        Vec3(1.0, 0.0, 0.0),
+        Vec3(0.0, 0.0, 1.0),
+        Arc::new(DiffuseLight::new(Vec3(8.0, 8.0, 8.0))),
+    )));
+
+    scene.add_light(Arc::new(SpotLight::new(
+        Vec3(0.0, 6.0, 3.0),
+        Vec3(0.0, -6.0, -3.0),
+        Vec3(60.0, 50.0, 40.0),
+        15.0,
+        5.0,
+    )));
+    scene.add_light(Arc::new(PointLight::new(
+        Vec3(6.0, 1.5, 3.0),
+        Vec3(2.0, 0.5, 0.2),
+    )));
+
+    // moonlight
+    scene.add_light(Arc::new(DirectionalLight::new(
+        Vec3(-1.0, -1.0, 0.5),
+        Vec3(0.04, 0.05, 0.08),
+        0.5,
+    )));
+
+    scene
+}
+
+/// The spheres of the lights scene in the late afternoon sun.
+fn outdoor_scene() -> Scene {
+    let mut scene = Scene::new(HittableList::default());
+
+    let sky = PreethamSky::new(Vec3(-1.0, 0.5, 0.8), 3.0, Vec3(0.3, 0.3, 0.3));
+    scene.set_background(Background::Physical(Box::new(sky)));
+    scene.add_light(Arc::new(sky.sun()));
+
+    scene.add(Arc::new(Sphere::new(
+        Vec3(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new(Vec3(0.5, 0.5, 0.5))),
+    )));
+    scene.add(Arc::new(Sphere::new(
+        Vec3(-4.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::new(Vec3(0.4, 0.2, 0.1))),
+    )));
+    scene.add(Arc::new(Sphere::new(
+        Vec3(0.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+    scene.add(Arc::new(Sphere::new(
+        Vec3(4.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Conductor::gold(0.2)),
+    )));
+
+    scene
+}
+
+fn build_scene(name: &str, aspect_ratio: f64) -> Result<(Scene, Camera), String> {
+    match name {
+        "random" => Ok((random_scene().into(), random_scene_camera(aspect_ratio))),
+        "lights" => Ok((lights_scene(), random_scene_camera(aspect_ratio))),
+        "outdoor" => Ok((outdoor_scene(), random_scene_camera(aspect_ratio))),
+        _ => Err(format!("unknown scene '{}'\n{}", name, USAGE)),
+    }
+}
+
+fn build_integrator(name: &str) -> Result<Box<dyn Integrator>, String> {
+    match name {
+        "path" => Ok(Box::new(PathTracer)),
+        "ao" => Ok(Box::new(AmbientOcclusion::default())),
+        "debug-normal" => Ok(Box::new(DebugIntegrator::new(DebugMode::ShadingNormal))),
+        "debug-geometric-normal" => Ok(Box::new(DebugIntegrator::new(DebugMode::GeometricNormal))),
+        "debug-depth" => Ok(Box::new(DebugIntegrator::new(DebugMode::Depth))),
+        "debug-front-face" => Ok(Box::new(DebugIntegrator::new(DebugMode::FrontFace))),
+        "debug-uv" => Ok(Box::new(DebugIntegrator::new(DebugMode::Uv))),
+        "debug-material" => Ok(Box::new(DebugIntegrator::new(DebugMode::Material))),
+        "debug-object" => Ok(Box::new(DebugIntegrator::new(DebugMode::Object))),
+        _ => Err(format!("unknown integrator '{}'\n{}", name, USAGE)),
+    }
+}
+
+fn build_sampler(name: &str) -> Result<Box<dyn Sampler>, String> {
+    match name {
+        "independent" => Ok(Box::new(IndependentSampler)),
+        "stratified" => Ok(Box::new(StratifiedSampler)),
+        "halton" => Ok(Box::new(HaltonSampler)),
+        "sobol" => Ok(Box::new(SobolSampler)),
+        "blue-noise" => Ok(Box::new(BlueNoiseSampler)),
+        _ => Err(format!("unknown sampler '{}'\n{}", name, USAGE)),
+    }
+}
+
+fn build_filter(name: &str, radius: Option<f64>) -> Result<Filter, String> {
+    let kind = match name {
+        "box" => FilterKind::Box,
+        "tent" => FilterKind::Tent,
+        "gaussian" => FilterKind::Gaussian,
+        "mitchell" => FilterKind::MitchellNetravali,
+        "lanczos" => FilterKind::Lanczos,
+        _ => return Err(format!("unknown filter '{}'\n{}", name, USAGE)),
+    };
+
+    let filter = Filter::new(kind);
+    Ok(match radius {
+        Some(radius) => filter.with_radius(radius),
+        None => filter,
+    })
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("\nerror: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let options = Options::parse(std::env::args().skip(1))?;
+
     // image data
     let aspect_ratio = 16.0 / 9.0;
     let image_width: usize = 400;
-    let samples_per_pixel = 100;
+    let samples_per_pixel = options.samples_per_pixel;
     let max_depth = 50;
 
     let image_height = (image_width as f64 / aspect_ratio) as usize;
 
-    let image = PpmImage::new(image_width, image_height, ppm_image::PpmImageType::PlainPpm);
+    let image = PpmImage::new(image_width, image_height, PpmImageType::PlainPpm)?;
+
+    // world and camera
+    let scene_start = Instant::now();
+    let (world, cam) = build_scene(&options.scene, aspect_ratio)?;
+    let scene_build = scene_start.elapsed();
 
-    // world
-    let world = random_scene();
-    let renderer = Renderer::new(world, image);
+    let integrator = build_integrator(&options.integrator)?;
+    let sampler = build_sampler(&options.sampler)?;
+    let filter = build_filter(&options.filter, options.filter_radius)?;
+
+    let mut renderer = Renderer::new(world, image)
+        .with_integrator(integrator)
+        .with_sampler(sampler)
+        .with_filter(filter)
+        .with_progress(Box::new(TerminalProgress::default()));
+    if options.denoised_output.is_some() {
+        renderer = renderer.with_denoiser(Denoiser::default());
+    }
+
+    // first ctrl-c stops the render and keeps what is done so far, the second one exits right away
+    let token = renderer.cancellation_token();
+    ctrlc::set_handler(move || {
+        if token.is_cancelled() {
+            std::process::exit(130);
+        }
+        token.cancel();
+    })?;
+    let token = renderer.cancellation_token();
+
+    let result = renderer.render(&cam, samples_per_pixel, max_depth)?;
+
+    if token.is_cancelled() {
+        eprintln!("Render cancelled, writing partial image");
+    }
+
+    let output_start = Instant::now();
+    result.image.save(&options.output)?;
+    if let (Some(path), Some(denoised)) = (&options.denoised_output, &result.denoised) {
+        denoised.save(path)?;
+    }
+    if let Some(path) = &options.aovs {
+        result.framebuffer.save_exr(path)?;
+    }
 
-    let result = renderer.render(samples_per_pixel, max_depth, aspect_ratio);
+    eprintln!("Done!");
 
-    // TODO: Pass image name via command line
-    let output_file = std::fs::File::create("image.ppm").unwrap();
-    result.write_to_file(output_file).unwrap();
+    // stdout only gets the statistics, so they can be piped into other tools
+    let mut stats = result.stats;
+    stats.scene_build = scene_build;
+    stats.output = output_start.elapsed();
+    println!("{}", stats.to_json());
 
-    println!("\nDone!");
+    Ok(())
 }
@@ -1,11 +1,150 @@
-use crate::{hittable::HitRecord, ray::Ray, vec3::Color};
+use crate::{
+    hittable::HitRecord,
+    onb::Onb,
+    ray::Ray,
+    sampler::SampleStream,
+    vec3::{Color, Vec3},
+};
+
+/// Direction picked by [`Material::scatter`] together with what is needed to weight it.
+///
+/// Specular directions are picked with a delta distribution that the BSDF shares, so for
+/// them `bsdf` is the fraction of light that is carried along and `pdf` is 1.
+#[derive(Debug, Clone, Copy)]
+pub struct ScatterRecord {
+    pub direction: Vec3, // unit vector the light arrives from
+    pub bsdf: Color,     // for light arriving from the direction and leaving along -r_in
+    pub pdf: f64,        // per solid angle, 1 for specular directions
+    pub specular: bool,  // picked by a delta distribution, can't be reached by light sampling
+}
+
+impl ScatterRecord {
+    /// Factor by which the light arriving along the scattered ray is weighted.
+    pub fn attenuation(&self, normal: Vec3) -> Color {
+        if self.specular {
+            return self.bsdf;
+        }
+
+        self.bsdf * (Vec3::dot(self.direction, normal).abs() / self.pdf)
+    }
+}
+
+/// Outgoing and incoming direction in the local frame of the surface hit by `r_in`. Its z
+/// axis is the normal on the side the ray arrives from, so the outgoing one is above it.
+pub(crate) fn local_directions(r_in: Ray, rec: &HitRecord, direction: Vec3) -> (Vec3, Vec3) {
+    let basis = Onb::from_w(rec.normal);
+    (
+        basis.to_local(-Vec3::unit_vector(r_in.direction())),
+        basis.to_local(Vec3::unit_vector(direction)),
+    )
+}
 
 pub trait Material: Send + Sync {
+    /// Picks the direction the incoming ray `r_in` continues in, or `None` if the light is
+    /// absorbed.
     fn scatter(
         &self,
         r_in: Ray,
         rec: &HitRecord,
-        attenuation: &mut Color,
-        scattered: &mut Ray,
-    ) -> bool;
+        samples: &mut SampleStream,
+    ) -> Option<ScatterRecord>;
+
+    /// Light emitted by the surface towards the incoming ray.
+    fn emitted(&self, _rec: &HitRecord) -> Color {
+        Color::default()
+    }
+
+    /// Returns `true` if the material can't be evaluated with [`Material::bsdf`], e.g.
+    /// mirrors and glass that only scatter into a single direction.
+    ///
+    /// Specular surfaces are not lit by light sampling.
+    fn is_specular(&self) -> bool {
+        true
+    }
+
+    /// Returns `true` if the material reflects light diffusely, this only decides which
+    /// [`crate::Aov`] its reflections end up in.
+    fn is_diffuse(&self) -> bool {
+        false
+    }
+
+    /// Value of the BSDF for light arriving from `direction` and leaving along `-r_in`.
+    fn bsdf(&self, _r_in: Ray, _rec: &HitRecord, _direction: Vec3) -> Color {
+        Color::default()
+    }
+
+    /// Probability density (per solid angle) of [`Material::scatter`] picking `direction`.
+    fn pdf(&self, _r_in: Ray, _rec: &HitRecord, _direction: Vec3) -> f64 {
+        0.0
+    }
+}
+
+/// Monte Carlo checks shared by the tests of the materials.
+#[cfg(test)]
+pub(crate) mod checks {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::{independent_sampler::IndependentSampler, stratified_sampler::StratifiedSampler};
+
+    /// Views from above at normal incidence, at an angle and at a grazing angle.
+    pub(crate) const VIEW_DIRECTIONS: [Vec3; 3] = [
+        Vec3(0.0, 0.0, 1.0),
+        Vec3(0.8, 0.48, 0.36),
+        Vec3(0.0, 0.995, 0.0998749217771909),
+    ];
+
+    /// Views from below, for glass at an angle below and above the critical one.
+    pub(crate) const INSIDE_DIRECTIONS: [Vec3; 2] = [Vec3(0.0, 0.6, -0.8), Vec3(0.0, 0.8, -0.6)];
+
+    /// Ray arriving from `wo` at a surface in the xy plane whose outward normal is z, hitting
+    /// its back face if `wo` is below it.
+    pub(crate) fn hit(wo: Vec3) -> (Ray, HitRecord) {
+        let r_in = Ray::new(wo, -wo, 0.0);
+        let mut rec = HitRecord::default();
+        rec.set_front_face(r_in, Vec3(0.0, 0.0, 1.0));
+        (r_in, rec)
+    }
+
+    /// Stratified directions spread uniformly over the whole sphere.
+    fn uniform_directions(count: usize) -> impl Iterator<Item = Vec3> {
+        (0..count).map(move |index| {
+            let mut samples = SampleStream::new(&StratifiedSampler, 3, index, count);
+            samples.start_bounce(0);
+            Vec3::sample_unit_vector(samples.get_2d())
+        })
+    }
+
+    /// Integral of [`Material::pdf`] over all directions.
+    pub(crate) fn pdf_integral(mat: &dyn Material, r_in: Ray, rec: &HitRecord) -> f64 {
+        let count = 200_000;
+        let sum: f64 = uniform_directions(count)
+            .map(|direction| mat.pdf(r_in, rec, direction))
+            .sum();
+        4.0 * PI * sum / count as f64
+    }
+
+    /// Fraction of the light arriving from all directions that leaves along `-r_in`,
+    /// estimated from the directions picked by [`Material::scatter`] and from uniformly
+    /// spread directions evaluated with [`Material::bsdf`].
+    pub(crate) fn albedo_estimates(mat: &dyn Material, r_in: Ray, rec: &HitRecord) -> (f64, f64) {
+        let count = 200_000;
+        let mut sampled = 0.0;
+        for index in 0..count {
+            let mut samples = SampleStream::new(&IndependentSampler, 5, index, count);
+            samples.start_bounce(0);
+            if let Some(scatter) = mat.scatter(r_in, rec, &mut samples) {
+                sampled += scatter.attenuation(rec.normal).luminance();
+            }
+        }
+
+        let uniform: f64 = uniform_directions(count)
+            .map(|direction| {
+                let cos = Vec3::dot(direction, rec.normal).abs();
+                mat.bsdf(r_in, rec, direction).luminance() * cos
+            })
+            .sum();
+
+        (sampled / count as f64, 4.0 * PI * uniform / count as f64)
+    }
 }
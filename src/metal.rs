@@ -1,7 +1,10 @@
+use std::f64::consts::PI;
+
 use crate::{
     hittable::HitRecord,
-    material::Material,
+    material::{Material, ScatterRecord},
     ray::Ray,
+    sampler::SampleStream,
     vec3::{Color, Vec3},
 };
 
@@ -24,16 +27,68 @@ impl Material for Metal {
         &self,
         r_in: Ray,
         rec: &HitRecord,
-        attenuation: &mut Color,
-        scattered: &mut Ray,
-    ) -> bool {
+        samples: &mut SampleStream,
+    ) -> Option<ScatterRecord> {
         let reflected = Vec3::reflect(Vec3::unit_vector(r_in.direction()), rec.normal);
-        *scattered = Ray::new(
-            rec.p,
-            reflected + self.fuzz * Vec3::random_in_unit_sphere(),
-            r_in.time(),
+        let direction = Vec3::unit_vector(
+            reflected + self.fuzz * Vec3::sample_in_unit_sphere(samples.get_2d(), samples.get_1d()),
         );
-        *attenuation = self.albedo;
-        Vec3::dot(scattered.direction(), rec.normal) > 0.0
+        if Vec3::dot(direction, rec.normal) <= 0.0 {
+            return None;
+        }
+
+        if self.is_specular() {
+            return Some(ScatterRecord {
+                direction,
+                bsdf: self.albedo,
+                pdf: 1.0,
+                specular: true,
+            });
+        }
+
+        let pdf = self.pdf(r_in, rec, direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(ScatterRecord {
+            direction,
+            bsdf: self.bsdf(r_in, rec, direction),
+            pdf,
+            specular: false,
+        })
+    }
+
+    fn is_specular(&self) -> bool {
+        self.fuzz == 0.0
+    }
+
+    fn bsdf(&self, r_in: Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        let cosine = Vec3::dot(Vec3::unit_vector(direction), rec.normal);
+        if cosine <= 0.0 {
+            return Color::default();
+        }
+
+        // chosen so that bsdf * cos / pdf gives the albedo, just like scatter does
+        self.albedo * (self.pdf(r_in, rec, direction) / cosine)
+    }
+
+    fn pdf(&self, r_in: Ray, rec: &HitRecord, direction: Vec3) -> f64 {
+        if self.is_specular() {
+            return 0.0;
+        }
+
+        // scatter picks a uniform point in a ball with radius fuzz around the tip of the
+        // reflected unit vector, so the density of a direction is the integral of t^2 over
+        // the part of the ray t * direction that lies inside that ball, divided by its volume
+        let reflected = Vec3::reflect(Vec3::unit_vector(r_in.direction()), rec.normal);
+        let c = Vec3::dot(Vec3::unit_vector(direction), reflected);
+        let discriminant = c * c - 1.0 + self.fuzz * self.fuzz;
+        if c <= 0.0 || discriminant <= 0.0 {
+            return 0.0;
+        }
+
+        let s = discriminant.sqrt();
+        (3.0 * s * c * c + s * s * s) / (2.0 * PI * self.fuzz.powi(3))
     }
 }
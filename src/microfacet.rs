@@ -0,0 +1,193 @@
+use std::f64::consts::PI;
+
+use crate::vec3::Vec3;
+
+/// Trowbridge-Reitz (GGX) distribution of microfacet normals, the building block of rough
+/// materials.
+///
+/// All directions are in the local frame of the surface, with the normal along z.
+#[derive(Debug, Clone, Copy)]
+pub struct TrowbridgeReitz {
+    alpha: f64,
+}
+
+impl TrowbridgeReitz {
+    /// `roughness` is perceptually linear, the width of the distribution is its square.
+    pub fn new(roughness: f64) -> Self {
+        let roughness = roughness.clamp(0.0, 1.0);
+        Self {
+            alpha: f64::max(roughness * roughness, 1e-4),
+        }
+    }
+
+    /// Returns `true` if the surface is so smooth that it should be treated as a mirror.
+    pub fn is_smooth(&self) -> bool {
+        self.alpha < 1e-3
+    }
+
+    /// Density of microfacets with normal `h`, per area of the macro surface.
+    pub fn d(&self, h: Vec3) -> f64 {
+        if h.z() <= 0.0 {
+            return 0.0;
+        }
+
+        let alpha2 = self.alpha * self.alpha;
+        let cos2 = h.z() * h.z();
+        let denominator = cos2 * (alpha2 - 1.0) + 1.0;
+        alpha2 / (PI * denominator * denominator)
+    }
+
+    /// Smith's auxiliary function, the area of microfacets hidden from `w` relative to the
+    /// visible ones.
+    fn lambda(&self, w: Vec3) -> f64 {
+        let cos2 = w.z() * w.z();
+        if cos2 == 0.0 {
+            return f64::INFINITY;
+        }
+
+        let tan2 = (1.0 - cos2).max(0.0) / cos2;
+        (-1.0 + (1.0 + self.alpha * self.alpha * tan2).sqrt()) / 2.0
+    }
+
+    /// Fraction of microfacets visible from `w`.
+    pub fn g1(&self, w: Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// Fraction of microfacets visible from both `wo` and `wi`, height correlated.
+    pub fn g(&self, wo: Vec3, wi: Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// Density of the microfacet normals seen from `wo`, which [`Self::sample_visible_normal`]
+    /// samples.
+    pub fn visible_normal_pdf(&self, wo: Vec3, h: Vec3) -> f64 {
+        if wo.z() <= 0.0 {
+            return 0.0;
+        }
+
+        self.g1(wo) * f64::max(Vec3::dot(wo, h), 0.0) * self.d(h) / wo.z()
+    }
+
+    /// Maps `u` to a microfacet normal seen from `wo`, after Heitz, "Sampling the GGX
+    /// Distribution of Visible Normals". `wo` has to be above the surface.
+    pub fn sample_visible_normal(&self, wo: Vec3, u: (f64, f64)) -> Vec3 {
+        // stretch the view direction so the distribution becomes a hemisphere
+        let v = Vec3::unit_vector(Vec3(self.alpha * wo.x(), self.alpha * wo.y(), wo.z()));
+
+        let len2 = v.x() * v.x() + v.y() * v.y();
+        let t1 = if len2 > 0.0 {
+            Vec3(-v.y(), v.x(), 0.0) / len2.sqrt()
+        } else {
+            Vec3(1.0, 0.0, 0.0)
+        };
+        let t2 = Vec3::cross(v, t1);
+
+        // uniform point on the disk, squeezed into the part of it that is visible
+        let r = u.0.sqrt();
+        let phi = 2.0 * PI * u.1;
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + v.z());
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * r * phi.sin();
+        let p3 = (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt();
+
+        let n = p1 * t1 + p2 * t2 + p3 * v;
+        Vec3::unit_vector(Vec3(
+            self.alpha * n.x(),
+            self.alpha * n.y(),
+            f64::max(n.z(), 1e-6),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{independent_sampler::IndependentSampler, material::checks, sampler::SampleStream};
+
+    /// Integrates `f` weighted by the visible normal density over the hemisphere, with the
+    /// midpoint rule in `theta` and `phi`.
+    fn integrate(
+        distribution: TrowbridgeReitz,
+        wo: Vec3,
+        f: impl Fn(Vec3) -> f64,
+        steps: usize,
+    ) -> f64 {
+        let (theta_step, phi_step) = (0.5 * PI / steps as f64, 2.0 * PI / steps as f64);
+        let mut sum = 0.0;
+        for a in 0..steps {
+            let theta = (a as f64 + 0.5) * theta_step;
+            for b in 0..steps {
+                let phi = (b as f64 + 0.5) * phi_step;
+                let h = Vec3(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                );
+                sum += f(h) * distribution.visible_normal_pdf(wo, h) * theta.sin();
+            }
+        }
+        sum * theta_step * phi_step
+    }
+
+    /// Averages `f` over normals drawn with [`TrowbridgeReitz::sample_visible_normal`].
+    fn sample_mean(
+        distribution: TrowbridgeReitz,
+        wo: Vec3,
+        f: impl Fn(Vec3) -> f64,
+        count: usize,
+    ) -> f64 {
+        let sampler = IndependentSampler;
+        let mut sum = 0.0;
+        for index in 0..count {
+            let mut samples = SampleStream::new(&sampler, 7, index, count);
+            samples.start_bounce(0);
+            sum += f(distribution.sample_visible_normal(wo, samples.get_2d()));
+        }
+        sum / count as f64
+    }
+
+    #[test]
+    fn visible_normal_pdf_integrates_to_one() {
+        for roughness in [0.3, 0.6, 1.0] {
+            for wo in checks::VIEW_DIRECTIONS {
+                let distribution = TrowbridgeReitz::new(roughness);
+                let integral = integrate(distribution, wo, |_| 1.0, 400);
+                assert!((integral - 1.0).abs() < 0.01, "{roughness}: {integral}");
+            }
+        }
+    }
+
+    #[test]
+    fn sample_visible_normal_matches_its_pdf() {
+        let moments: [fn(Vec3) -> f64; 4] =
+            [|h| h.x(), |h| h.y(), |h| h.z(), |h| h.x() * h.x() * h.z()];
+
+        for roughness in [0.3, 0.6, 1.0] {
+            for wo in checks::VIEW_DIRECTIONS {
+                let distribution = TrowbridgeReitz::new(roughness);
+                for f in moments {
+                    let expected = integrate(distribution, wo, f, 400);
+                    let sampled = sample_mean(distribution, wo, f, 100_000);
+                    assert!(
+                        (expected - sampled).abs() < 0.01,
+                        "{roughness}: {expected} != {sampled}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sampled_normals_face_the_viewer() {
+        let distribution = TrowbridgeReitz::new(0.8);
+        let wo = Vec3::unit_vector(Vec3(0.9, 0.0, 0.1));
+        let facing = sample_mean(
+            distribution,
+            wo,
+            |h| (Vec3::dot(wo, h) >= 0.0) as u8 as f64,
+            10_000,
+        );
+        assert_eq!(facing, 1.0);
+    }
+}
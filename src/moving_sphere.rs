@@ -3,6 +3,7 @@ use std::sync::Arc;
 use crate::{
     hittable::{HitResult, Hittable},
     material::Material,
+    sphere::sphere_uv,
     vec3::{Point3, Vec3},
 };
 
@@ -75,9 +76,10 @@ impl Hittable for MovingSphere {
 
         rec.t = root;
         rec.p = r.at(rec.t);
-        rec.normal = (rec.p - self.center(r.time())) / self.radius();
         let outward_normal = (rec.p - self.center(r.time())) / self.radius();
         rec.set_front_face(r, outward_normal);
+        (rec.u, rec.v) = sphere_uv(outward_normal);
+        rec.object = self as *const Self as usize;
 
         HitResult::Hit(self.mat.clone())
     }
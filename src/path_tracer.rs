@@ -0,0 +1,285 @@
+use crate::{
+    aov::{Aov, Aovs},
+    hittable::{HitRecord, HitResult, Hittable},
+    integrator::Integrator,
+    material::Material,
+    ray::Ray,
+    sampler::SampleStream,
+    scene::Scene,
+    stats,
+    utils::clamp,
+    vec3::{Color, Vec3},
+};
+
+/// Number of bounces every path survives before Russian roulette may end it
+const ROULETTE_MIN_BOUNCES: usize = 3;
+
+/// Unidirectional path tracer that samples the lights at every diffuse hit and combines
+/// that with BSDF sampling using multiple importance sampling.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PathTracer;
+
+impl PathTracer {
+    /// Light of the scene lights arriving along `r` after it left the scene.
+    fn escaped_light(&self, scene: &Scene, r: Ray, bsdf_pdf: Option<f64>) -> Color {
+        let direction = Vec3::unit_vector(r.direction());
+        let mut color = Vec3(0.0, 0.0, 0.0);
+
+        for light in scene.lights() {
+            let weight = match bsdf_pdf {
+                Some(bsdf_pdf) => power_heuristic(bsdf_pdf, light.pdf(r.origin(), direction)),
+                None => 1.0,
+            };
+            color += weight * light.radiance(direction);
+        }
+
+        color
+    }
+
+    /// Light arriving at `rec` directly from the scene lights, each of them is sampled once.
+    fn sample_lights(
+        &self,
+        scene: &Scene,
+        r: Ray,
+        rec: &HitRecord,
+        mat: &dyn Material,
+        samples: &mut SampleStream,
+    ) -> Color {
+        let mut color = Vec3(0.0, 0.0, 0.0);
+
+        for light in scene.lights() {
+            let sample = match light.sample(rec.p, samples) {
+                Some(sample) => sample,
+                None => continue,
+            };
+
+            // lights behind the surface can still shine through it
+            let bsdf = mat.bsdf(r, rec, sample.direction);
+            if bsdf.near_zero() {
+                continue;
+            }
+
+            stats::count_shadow_ray();
+            let shadow_ray = Ray::new(rec.p, sample.direction, r.time());
+            let mut shadow_rec = HitRecord::default();
+            if scene
+                .world()
+                .hit(shadow_ray, 0.001, sample.distance - 1e-4, &mut shadow_rec)
+                .is_hit()
+            {
+                continue;
+            }
+
+            let weight = if light.is_delta() {
+                1.0
+            } else {
+                power_heuristic(sample.pdf, mat.pdf(r, rec, sample.direction))
+            };
+
+            let cos_surface = Vec3::dot(sample.direction, rec.normal).abs();
+            color += bsdf * sample.radiance * (weight * cos_surface / sample.pdf);
+        }
+
+        color
+    }
+
+    /// Estimates the light arriving at `rec` directly from a randomly picked area light.
+    fn sample_area_light(
+        &self,
+        scene: &Scene,
+        r: Ray,
+        rec: &HitRecord,
+        mat: &dyn Material,
+        samples: &mut SampleStream,
+    ) -> Color {
+        let lights = scene.area_lights();
+        if lights.is_empty() {
+            return Vec3(0.0, 0.0, 0.0);
+        }
+
+        let light_idx = (samples.get_1d() * lights.len() as f64) as usize;
+        let light = &lights[light_idx.min(lights.len() - 1)];
+        let direction = match light.sample_direction(rec.p, samples) {
+            Some(direction) => Vec3::unit_vector(direction),
+            None => return Vec3(0.0, 0.0, 0.0),
+        };
+
+        let bsdf = mat.bsdf(r, rec, direction);
+        if bsdf.near_zero() {
+            return Vec3(0.0, 0.0, 0.0);
+        }
+
+        // any of the lights could have picked this direction
+        let pdf = scene.area_light_pdf(rec.p, direction);
+        if pdf == 0.0 {
+            return Vec3(0.0, 0.0, 0.0);
+        }
+
+        // whatever the shadow ray hits first is what lights the surface
+        stats::count_shadow_ray();
+        let shadow_ray = Ray::new(rec.p, direction, r.time());
+        let mut light_rec = HitRecord::default();
+        let light_mat = match scene
+            .world()
+            .hit(shadow_ray, 0.001, f64::INFINITY, &mut light_rec)
+        {
+            HitResult::Hit(light_mat) => light_mat,
+            HitResult::Miss => return Vec3(0.0, 0.0, 0.0),
+        };
+
+        let weight = power_heuristic(pdf, mat.pdf(r, rec, direction));
+        let cos_surface = Vec3::dot(direction, rec.normal).abs();
+
+        bsdf * light_mat.emitted(&light_rec) * (weight * cos_surface / pdf)
+    }
+}
+
+impl Integrator for PathTracer {
+    fn li(&self, r: Ray, scene: &Scene, max_depth: usize, samples: &mut SampleStream) -> Color {
+        self.li_with_aovs(r, scene, max_depth, &mut Aovs::default(), samples)
+    }
+
+    /// Follows the path starting with the camera ray `r` and returns the light it carries back.
+    fn li_with_aovs(
+        &self,
+        r: Ray,
+        scene: &Scene,
+        max_depth: usize,
+        aovs: &mut Aovs,
+        samples: &mut SampleStream,
+    ) -> Color {
+        let mut color = Vec3(0.0, 0.0, 0.0);
+        let mut throughput = Vec3(1.0, 1.0, 1.0);
+        let mut r = r;
+
+        // density with which `r` was scattered into its direction, it is `None` for
+        // camera rays and rays coming from specular surfaces
+        let mut bsdf_pdf: Option<f64> = None;
+
+        // decides the light AOVs everything after the first hit ends up in
+        let mut first_hit_diffuse = false;
+
+        // if we exceed the ray bounce limit, no more light is gathered
+        for depth in 0..max_depth {
+            samples.start_bounce(depth);
+            let mut rec = HitRecord::default();
+
+            let mat = match scene.world().hit(r, 0.001, f64::INFINITY, &mut rec) {
+                HitResult::Hit(mat) => mat,
+                HitResult::Miss => {
+                    let escaped =
+                        scene.background().color(r) + self.escaped_light(scene, r, bsdf_pdf);
+                    add_light(
+                        &mut color,
+                        aovs,
+                        depth,
+                        first_hit_diffuse,
+                        throughput * escaped,
+                    );
+                    break;
+                }
+            };
+
+            if depth == 0 {
+                first_hit_diffuse = mat.is_diffuse();
+                aovs.set(Aov::Normal, rec.normal);
+                aovs.set_depth(rec.t * r.direction().length());
+            }
+
+            // lights could also have been sampled directly at the origin of the ray,
+            // so their contribution is shared between both strategies
+            let mut emitted = mat.emitted(&rec);
+            if let Some(bsdf_pdf) = bsdf_pdf {
+                if !emitted.near_zero() {
+                    let light_pdf = scene.area_light_pdf(r.origin(), r.direction());
+                    emitted *= power_heuristic(bsdf_pdf, light_pdf);
+                }
+            }
+            add_light(
+                &mut color,
+                aovs,
+                depth,
+                first_hit_diffuse,
+                throughput * emitted,
+            );
+
+            // scattering and roulette draw their numbers first, so they get the same
+            // dimensions no matter how many the light sampling uses
+            let scatter = mat.scatter(r, &rec, samples);
+            let roulette = samples.get_1d();
+
+            if !mat.is_specular() {
+                let direct = self.sample_area_light(scene, r, &rec, mat.as_ref(), samples)
+                    + self.sample_lights(scene, r, &rec, mat.as_ref(), samples);
+                add_light(
+                    &mut color,
+                    aovs,
+                    depth + 1,
+                    first_hit_diffuse,
+                    throughput * direct,
+                );
+            }
+
+            let scatter = match scatter {
+                Some(scatter) => scatter,
+                None => {
+                    if depth == 0 {
+                        aovs.set(Aov::Albedo, clamp_color(emitted));
+                    }
+                    break;
+                }
+            };
+            let attenuation = scatter.attenuation(rec.normal);
+            throughput *= attenuation;
+
+            if depth == 0 {
+                aovs.set(Aov::Albedo, attenuation);
+            }
+
+            // end paths that can't carry much light anymore, the survivors make up for them
+            if depth + 1 >= ROULETTE_MIN_BOUNCES {
+                let survival = f64::min(throughput.max_component(), 0.95);
+                if roulette >= survival {
+                    break;
+                }
+                throughput /= survival;
+            }
+
+            bsdf_pdf = if scatter.specular {
+                None
+            } else {
+                Some(scatter.pdf)
+            };
+            stats::count_secondary_ray();
+            r = Ray::new(rec.p, scatter.direction, r.time());
+        }
+
+        color
+    }
+}
+
+/// Adds `light` that was reflected `bounce` times to the path and its AOV.
+fn add_light(color: &mut Color, aovs: &mut Aovs, bounce: usize, diffuse: bool, light: Color) {
+    *color += light;
+    aovs.add(Aov::for_light(bounce, diffuse), light);
+}
+
+/// Emitters have no reflectance, their albedo is their color limited to one.
+fn clamp_color(c: Color) -> Color {
+    Vec3(
+        clamp(c.x(), 0.0, 1.0),
+        clamp(c.y(), 0.0, 1.0),
+        clamp(c.z(), 0.0, 1.0),
+    )
+}
+
+/// Multiple importance sampling weight for a sample taken with density `pdf`, when
+/// another strategy could have produced it with density `other_pdf`.
+fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let (a, b) = (pdf * pdf, other_pdf * other_pdf);
+    if a + b == 0.0 {
+        return 0.0;
+    }
+
+    a / (a + b)
+}
@@ -0,0 +1,44 @@
+use crate::{
+    light::{Falloff, Light, LightSample},
+    sampler::SampleStream,
+    vec3::{Color, Point3},
+};
+
+/// Shines equally into all directions from a single point.
+pub struct PointLight {
+    position: Point3,
+    intensity: Color,
+    falloff: Falloff,
+}
+
+impl PointLight {
+    pub fn new(position: Point3, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+            falloff: Falloff::default(),
+        }
+    }
+
+    pub fn with_falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, p: Point3, _samples: &mut SampleStream) -> Option<LightSample> {
+        let to_light = self.position - p;
+        let distance = to_light.length();
+        if distance == 0.0 {
+            return None;
+        }
+
+        Some(LightSample {
+            direction: to_light / distance,
+            distance,
+            radiance: self.intensity * self.falloff.attenuation(distance),
+            pdf: 1.0,
+        })
+    }
+}
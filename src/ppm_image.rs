@@ -1,4 +1,10 @@
-use std::{fs::File, io::Write};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use crate::error::{Error, Result};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum PpmImageType {
@@ -6,6 +12,7 @@ pub enum PpmImageType {
     PlainPpm,
 }
 
+#[derive(Clone)]
 pub struct PpmImage {
     width: usize,
     height: usize,
@@ -14,30 +21,53 @@ pub struct PpmImage {
 }
 
 impl PpmImage {
-    pub fn new(width: usize, height: usize, image_type: PpmImageType) -> Self {
-        assert!(
-            image_type == PpmImageType::PlainPpm,
-            "only plain ppm images supported for now"
-        );
+    pub fn new(width: usize, height: usize, image_type: PpmImageType) -> Result<Self> {
+        // only plain ppm images supported for now
+        if image_type != PpmImageType::PlainPpm {
+            return Err(Error::UnsupportedImageType(image_type));
+        }
+
+        if width < 2 || height < 2 {
+            return Err(Error::InvalidImageSize { width, height });
+        }
 
         let v = vec![(0, 0, 0); width * height];
 
-        PpmImage {
+        Ok(PpmImage {
             width,
             height,
             image_type,
             pixels: v,
-        }
+        })
     }
 
-    pub fn set_value(&mut self, idx: usize, color: (u8, u8, u8)) {
-        let px = self.pixels.get_mut(idx).expect("index out of range");
+    pub fn set_value(&mut self, idx: usize, color: (u8, u8, u8)) -> Result<()> {
+        let len = self.pixels.len();
+        let px = self
+            .pixels
+            .get_mut(idx)
+            .ok_or(Error::PixelOutOfRange { idx, len })?;
         *px = color;
+        Ok(())
     }
 
-    pub fn write_to_file(&self, file: File) -> std::io::Result<()> {
-        let mut file = file;
+    /// Creates (or truncates) the file at `path` and writes the image into it.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let to_error = |source| Error::File {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        let file = File::create(path).map_err(to_error)?;
+        self.write_to_file(BufWriter::new(file))
+            .map_err(|e| match e {
+                Error::Io(source) => to_error(source),
+                e => e,
+            })
+    }
 
+    pub fn write_to_file(&self, mut out: impl Write) -> Result<()> {
         let header = format!(
             "{}\n{} {}\n255\n",
             magic_number(self.image_type),
@@ -45,13 +75,13 @@ impl PpmImage {
             self.height
         );
 
-        file.write_all(header.as_bytes())?;
+        out.write_all(header.as_bytes())?;
 
         for pixels in &self.pixels {
-            writeln!(file, "{} {} {}", pixels.0, pixels.1, pixels.2)?;
+            writeln!(out, "{} {} {}", pixels.0, pixels.1, pixels.2)?;
         }
 
-        file.flush()?;
+        out.flush()?;
         Ok(())
     }
 
@@ -0,0 +1,503 @@
+use std::{f64::consts::PI, sync::OnceLock};
+
+use crate::{
+    fresnel,
+    gltf_material::GltfMaterial,
+    hittable::HitRecord,
+    material::{local_directions, Material, ScatterRecord},
+    microfacet::TrowbridgeReitz,
+    onb::Onb,
+    ray::Ray,
+    rough_dielectric::RoughDielectric,
+    sampler::SampleStream,
+    vec3::{Color, Vec3},
+};
+
+/// Below this roughness the highlights would become mirror reflections, which can't be
+/// mixed with the other lobes
+const MIN_ROUGHNESS: f64 = 0.05;
+
+/// Reflectance of the clear coat along the normal, a varnish with an index of refraction of 1.5
+const COAT_F0: f64 = 0.04;
+
+/// Below this sheen roughness the sheen would only show up at the very silhouette
+const MIN_SHEEN_ROUGHNESS: f64 = 0.07;
+
+/// Entries of the sheen albedo table along the cosine to the normal and the sheen roughness
+const SHEEN_TABLE_SIZE: (usize, usize) = (32, 16);
+
+/// Physically based material in the terms lookdev artists use: base color, metallic,
+/// roughness, specular, sheen, clear coat and transmission.
+///
+/// A Burley diffuse lobe, a GGX specular lobe and frosted glass are blended by the metallic
+/// and transmission parameters, and Charlie sheen and a GGX clear coat are layered on top.
+/// Every lobe can be sampled and evaluated, so the material works with light sampling.
+/// It follows the glTF metallic-roughness model and can be created from a [`GltfMaterial`].
+#[derive(Debug, Clone, Copy)]
+pub struct Principled {
+    base_color: Color,
+    metallic: f64,
+    roughness: f64,
+    specular: f64,
+    specular_color: Color,
+    ior: f64,
+    sheen: Color,
+    sheen_roughness: f64,
+    clearcoat: f64,
+    clearcoat_roughness: f64,
+    transmission: f64,
+    thin_walled: bool,
+    emission: Color,
+}
+
+impl Principled {
+    /// Rough dielectric with the given color, e.g. plastic.
+    pub fn new(base_color: Color) -> Self {
+        Self {
+            base_color,
+            metallic: 0.0,
+            roughness: 0.5,
+            specular: 1.0,
+            specular_color: Vec3(1.0, 1.0, 1.0),
+            ior: 1.5,
+            sheen: Color::default(),
+            sheen_roughness: 0.5,
+            clearcoat: 0.0,
+            clearcoat_roughness: 0.0,
+            transmission: 0.0,
+            thin_walled: false,
+            emission: Color::default(),
+        }
+    }
+
+    /// Converts the parameters of a glTF material.
+    ///
+    /// Without a thickness from `KHR_materials_volume` glTF treats transmissive materials as
+    /// thin-walled, so they let the light pass straight through.
+    pub fn from_gltf(material: &GltfMaterial) -> Self {
+        Self::new(material.base_color())
+            .with_metallic(material.metallic_factor)
+            .with_roughness(material.roughness_factor)
+            .with_specular(material.specular_factor)
+            .with_specular_color(material.specular_color())
+            .with_ior(material.ior)
+            .with_sheen(material.sheen_color())
+            .with_sheen_roughness(material.sheen_roughness_factor)
+            .with_clearcoat(
+                material.clearcoat_factor,
+                material.clearcoat_roughness_factor,
+            )
+            .with_transmission(material.transmission_factor)
+            .with_thin_walled(material.thickness_factor <= 0.0)
+            .with_emission(material.emission())
+    }
+
+    /// Blends from a dielectric at 0 to a metal at 1, which reflects its base color.
+    pub fn with_metallic(mut self, metallic: f64) -> Self {
+        self.metallic = metallic.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Perceptually linear roughness of the specular and transmission lobes.
+    pub fn with_roughness(mut self, roughness: f64) -> Self {
+        self.roughness = roughness.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Scales the reflectance of the dielectric specular lobe, 1 is what the index of
+    /// refraction gives.
+    pub fn with_specular(mut self, specular: f64) -> Self {
+        self.specular = specular.max(0.0);
+        self
+    }
+
+    /// Tints the reflectance of the dielectric specular lobe along the normal, the edges
+    /// still reflect white.
+    pub fn with_specular_color(mut self, specular_color: Color) -> Self {
+        self.specular_color = specular_color;
+        self
+    }
+
+    pub fn with_ior(mut self, ior: f64) -> Self {
+        self.ior = ior.max(1.0);
+        self
+    }
+
+    /// Soft retro reflective tint at grazing angles, for cloth and fabrics.
+    pub fn with_sheen(mut self, sheen: Color) -> Self {
+        self.sheen = sheen;
+        self
+    }
+
+    /// How far the sheen spreads from the grazing angles towards the normal.
+    pub fn with_sheen_roughness(mut self, sheen_roughness: f64) -> Self {
+        self.sheen_roughness = sheen_roughness.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Layers a varnish with the given strength and roughness on top.
+    pub fn with_clearcoat(mut self, clearcoat: f64, roughness: f64) -> Self {
+        self.clearcoat = clearcoat.clamp(0.0, 1.0);
+        self.clearcoat_roughness = roughness.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Blends the diffuse lobe into glass that lets the light pass through.
+    pub fn with_transmission(mut self, transmission: f64) -> Self {
+        self.transmission = transmission.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Makes the glass a thin wall that doesn't bend the light, instead of the surface of a
+    /// solid, see [`RoughDielectric::with_thin_walled`].
+    pub fn with_thin_walled(mut self, thin_walled: bool) -> Self {
+        self.thin_walled = thin_walled;
+        self
+    }
+
+    pub fn with_emission(mut self, emission: Color) -> Self {
+        self.emission = emission;
+        self
+    }
+
+    fn distribution(&self) -> TrowbridgeReitz {
+        TrowbridgeReitz::new(self.roughness.max(MIN_ROUGHNESS))
+    }
+
+    fn coat_distribution(&self) -> TrowbridgeReitz {
+        TrowbridgeReitz::new(self.clearcoat_roughness.max(MIN_ROUGHNESS))
+    }
+
+    fn glass(&self) -> RoughDielectric {
+        RoughDielectric::new(self.ior, self.roughness.max(MIN_ROUGHNESS))
+            .with_thin_walled(self.thin_walled)
+    }
+
+    /// Weight of the diffuse and sheen lobes, the part that is neither metal nor glass.
+    fn dielectric_weight(&self) -> f64 {
+        (1.0 - self.metallic) * (1.0 - self.transmission)
+    }
+
+    fn glass_weight(&self) -> f64 {
+        (1.0 - self.metallic) * self.transmission
+    }
+
+    /// Reflectance of the dielectric specular lobe. Like in `KHR_materials_specular` the
+    /// color tints the reflectance at normal incidence and the factor scales all of it.
+    fn dielectric_fresnel(&self, cos_theta: f64) -> Color {
+        let f0 = ((self.ior - 1.0) / (self.ior + 1.0)).powi(2);
+        let f0 = |tint: f64| (f0 * tint).min(1.0);
+        let c = self.specular_color;
+        self.specular * fresnel::schlick(cos_theta, Vec3(f0(c.x()), f0(c.y()), f0(c.z())))
+    }
+
+    /// Reflectance of the specular lobe, blended between the dielectric and the metal.
+    fn specular_fresnel(&self, cos_theta: f64) -> Color {
+        self.dielectric_weight() * self.dielectric_fresnel(cos_theta)
+            + self.metallic * fresnel::schlick(cos_theta, self.base_color)
+    }
+
+    /// Part of the light that the sheen leaves to the layers below, as glTF scales them.
+    fn sheen_scaling(&self, cos_o: f64, cos_i: f64) -> f64 {
+        let sheen = self.sheen.max_component();
+        if sheen <= 0.0 {
+            return 1.0;
+        }
+
+        f64::min(
+            1.0 - sheen * sheen_albedo(cos_o, self.sheen_roughness),
+            1.0 - sheen * sheen_albedo(cos_i, self.sheen_roughness),
+        )
+        .max(0.0)
+    }
+
+    /// Light reflected by the clear coat, which the layers below don't get.
+    fn coat_fresnel(&self, cos_theta: f64) -> f64 {
+        self.clearcoat * fresnel::schlick(cos_theta, Vec3(COAT_F0, COAT_F0, COAT_F0)).x()
+    }
+
+    /// Probabilities of sampling the diffuse, specular, glass and coat lobes, roughly in
+    /// proportion to how much light they reflect towards `wo`.
+    fn lobe_probabilities(&self, wo: Vec3) -> [f64; 4] {
+        let weights = [
+            self.dielectric_weight() * self.base_color.luminance() + self.sheen.luminance(),
+            self.specular_fresnel(wo.z()).luminance(),
+            self.glass_weight(),
+            self.coat_fresnel(wo.z()),
+        ];
+
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return [1.0, 0.0, 0.0, 0.0];
+        }
+        weights.map(|weight| weight / total)
+    }
+}
+
+impl Material for Principled {
+    fn scatter(
+        &self,
+        r_in: Ray,
+        rec: &HitRecord,
+        samples: &mut SampleStream,
+    ) -> Option<ScatterRecord> {
+        let basis = Onb::from_w(rec.normal);
+        let wo = basis.to_local(-Vec3::unit_vector(r_in.direction()));
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        let [diffuse, specular, glass, _] = self.lobe_probabilities(wo);
+        let lobe = samples.get_1d();
+        let direction = if lobe < diffuse {
+            basis.local(Vec3::sample_cosine_direction(samples.get_2d()))
+        } else if lobe < diffuse + specular {
+            let h = self
+                .distribution()
+                .sample_visible_normal(wo, samples.get_2d());
+            basis.local(Vec3::reflect(-wo, h))
+        } else if lobe < diffuse + specular + glass {
+            self.glass().scatter(r_in, rec, samples)?.direction
+        } else {
+            let h = self
+                .coat_distribution()
+                .sample_visible_normal(wo, samples.get_2d());
+            basis.local(Vec3::reflect(-wo, h))
+        };
+
+        // the density of all lobes together, as any of them could have picked the direction
+        let pdf = self.pdf(r_in, rec, direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(ScatterRecord {
+            direction,
+            bsdf: self.bsdf(r_in, rec, direction),
+            pdf,
+            specular: false,
+        })
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        if rec.front_face() {
+            self.emission
+        } else {
+            Color::default()
+        }
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+
+    fn is_diffuse(&self) -> bool {
+        self.dielectric_weight() >= 0.5
+    }
+
+    fn bsdf(&self, r_in: Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        let (wo, wi) = local_directions(r_in, rec, direction);
+        if wo.z() <= 0.0 || wi.z() == 0.0 {
+            return Color::default();
+        }
+
+        let mut value = self.glass_weight() * self.glass().bsdf(r_in, rec, direction);
+        if wi.z() < 0.0 {
+            // the base color tints the light passing through the glass, not what it reflects
+            value *= self.base_color;
+        } else {
+            let h = Vec3::unit_vector(wo + wi);
+            let cos_d = Vec3::dot(wi, h);
+
+            // Burley's diffuse, which gets brighter at grazing angles on rough surfaces
+            let fd90 = 0.5 + 2.0 * self.roughness * cos_d * cos_d;
+            let retro = |cos: f64| 1.0 + (fd90 - 1.0) * (1.0 - cos).powi(5);
+            let diffuse = self.base_color * (retro(wo.z()) * retro(wi.z()) / PI);
+            // what the specular lobe reflects doesn't reach the diffuse one
+            let transmitted = 1.0 - self.dielectric_fresnel(cos_d).max_component();
+            value += self.dielectric_weight() * transmitted * diffuse;
+
+            let distribution = self.distribution();
+            value += self.specular_fresnel(cos_d)
+                * (distribution.d(h) * distribution.g(wo, wi) / (4.0 * wo.z() * wi.z()));
+        }
+
+        // the sheen takes its share of the light like the coat does
+        value *= self.sheen_scaling(wo.z(), wi.z().abs());
+        if wi.z() > 0.0 {
+            value += self.sheen * charlie(wo, wi, self.sheen_roughness);
+        }
+
+        // the coat takes its share of the light before the layers below get any
+        value *= 1.0 - self.coat_fresnel(wo.z());
+        if wi.z() > 0.0 && self.clearcoat > 0.0 {
+            let h = Vec3::unit_vector(wo + wi);
+            let distribution = self.coat_distribution();
+            let coat =
+                self.coat_fresnel(Vec3::dot(wo, h)) * distribution.d(h) * distribution.g(wo, wi)
+                    / (4.0 * wo.z() * wi.z());
+            value += Vec3(coat, coat, coat);
+        }
+
+        value
+    }
+
+    fn pdf(&self, r_in: Ray, rec: &HitRecord, direction: Vec3) -> f64 {
+        let (wo, wi) = local_directions(r_in, rec, direction);
+        if wo.z() <= 0.0 {
+            return 0.0;
+        }
+
+        let [diffuse, specular, glass, coat] = self.lobe_probabilities(wo);
+        let mut pdf = glass * self.glass().pdf(r_in, rec, direction);
+        if wi.z() > 0.0 {
+            let h = Vec3::unit_vector(wo + wi);
+            let reflection = |distribution: TrowbridgeReitz| {
+                distribution.visible_normal_pdf(wo, h) / (4.0 * Vec3::dot(wo, h))
+            };
+
+            pdf += diffuse * wi.z() / PI
+                + specular * reflection(self.distribution())
+                + coat * reflection(self.coat_distribution());
+        }
+
+        pdf
+    }
+}
+
+/// Charlie sheen of Estevez and Kulla with the visibility of Neubelt and Pettineo, like
+/// glTF uses it, for a white sheen color.
+fn charlie(wo: Vec3, wi: Vec3, sheen_roughness: f64) -> f64 {
+    let alpha = sheen_roughness.max(MIN_SHEEN_ROUGHNESS).powi(2);
+    let h = Vec3::unit_vector(wo + wi);
+    let sin2 = (1.0 - h.z() * h.z()).max(0.0);
+    let d = (2.0 + 1.0 / alpha) * sin2.powf(0.5 / alpha) / (2.0 * PI);
+    let v = 1.0 / (4.0 * (wi.z() + wo.z() - wi.z() * wo.z()));
+    d * v
+}
+
+/// Fraction of the light arriving at `cos_theta` to the normal that a white sheen reflects,
+/// interpolated from a table computed on first use.
+fn sheen_albedo(cos_theta: f64, sheen_roughness: f64) -> f64 {
+    static TABLE: OnceLock<Vec<f64>> = OnceLock::new();
+    let table = TABLE.get_or_init(sheen_albedo_table);
+
+    let (columns, rows) = SHEEN_TABLE_SIZE;
+    let x = cos_theta.clamp(0.0, 1.0) * (columns - 1) as f64;
+    let y = sheen_roughness.clamp(0.0, 1.0) * (rows - 1) as f64;
+    let (x0, y0) = ((x as usize).min(columns - 2), (y as usize).min(rows - 2));
+    let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+
+    let at = |x: usize, y: usize| table[y * columns + x];
+    let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+    lerp(
+        lerp(at(x0, y0), at(x0 + 1, y0), tx),
+        lerp(at(x0, y0 + 1), at(x0 + 1, y0 + 1), tx),
+        ty,
+    )
+}
+
+/// Integrates the sheen over the hemisphere for every entry of the table, with the midpoint
+/// rule in `theta` and `phi`. The sheen is symmetric in `phi`, so half of it is enough.
+fn sheen_albedo_table() -> Vec<f64> {
+    let (columns, rows) = SHEEN_TABLE_SIZE;
+    let (theta_steps, phi_steps) = (96, 48);
+    let (theta_step, phi_step) = (0.5 * PI / theta_steps as f64, PI / phi_steps as f64);
+
+    let mut table = Vec::with_capacity(columns * rows);
+    for y in 0..rows {
+        let sheen_roughness = y as f64 / (rows - 1) as f64;
+        for x in 0..columns {
+            let cos_o = x as f64 / (columns - 1) as f64;
+            let wo = Vec3((1.0 - cos_o * cos_o).sqrt(), 0.0, cos_o);
+
+            let mut sum = 0.0;
+            for a in 0..theta_steps {
+                let theta = (a as f64 + 0.5) * theta_step;
+                for b in 0..phi_steps {
+                    let phi = (b as f64 + 0.5) * phi_step;
+                    let wi = Vec3(
+                        theta.sin() * phi.cos(),
+                        theta.sin() * phi.sin(),
+                        theta.cos(),
+                    );
+                    sum += charlie(wo, wi, sheen_roughness) * wi.z() * theta.sin();
+                }
+            }
+            table.push(2.0 * sum * theta_step * phi_step);
+        }
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{independent_sampler::IndependentSampler, material::checks};
+
+    fn materials() -> [Principled; 4] {
+        let white = Vec3(1.0, 1.0, 1.0);
+        [
+            Principled::new(Vec3(0.8, 0.3, 0.2)).with_specular_color(Vec3(0.2, 0.5, 1.0)),
+            Principled::new(Vec3(0.2, 0.2, 0.6))
+                .with_sheen(white)
+                .with_sheen_roughness(0.3),
+            Principled::new(white)
+                .with_metallic(0.5)
+                .with_clearcoat(1.0, 0.4),
+            Principled::new(white)
+                .with_roughness(0.7)
+                .with_transmission(1.0)
+                .with_thin_walled(true),
+        ]
+    }
+
+    #[test]
+    fn scatter_matches_bsdf() {
+        for (i, mat) in materials().iter().enumerate() {
+            for wo in checks::VIEW_DIRECTIONS {
+                let (r_in, rec) = checks::hit(wo);
+                let (sampled, uniform) = checks::albedo_estimates(mat, r_in, &rec);
+                assert!(
+                    (sampled - uniform).abs() < 0.02,
+                    "{i}, {wo:?}: {sampled} != {uniform}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn base_color_tints_transmitted_light() {
+        // light that scatter sends through the surface, per color channel
+        let transmitted = |base_color: Color| {
+            let mat = Principled::new(base_color)
+                .with_roughness(0.5)
+                .with_transmission(1.0);
+            let (r_in, rec) = checks::hit(Vec3(0.8, 0.48, 0.36));
+            let count = 20_000;
+            let mut sum = Color::default();
+            for index in 0..count {
+                let mut samples = SampleStream::new(&IndependentSampler, 5, index, count);
+                samples.start_bounce(0);
+                match mat.scatter(r_in, &rec, &mut samples) {
+                    Some(scatter) if scatter.direction.z() < 0.0 => {
+                        sum += scatter.attenuation(rec.normal)
+                    }
+                    _ => {}
+                }
+            }
+            sum / count as f64
+        };
+
+        let tint = Vec3(1.0, 0.5, 0.25);
+        let clear = transmitted(Vec3(1.0, 1.0, 1.0));
+        let tinted = transmitted(tint);
+        assert!(clear.x() > 0.5, "{clear:?}");
+        let expected = clear * tint;
+        assert!(
+            (tinted - expected).length() < 1e-9,
+            "{tinted:?} != {expected:?}"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use std::{io::Write, time::Duration};
+
+/// Snapshot of a running render, handed to a [`ProgressObserver`] on every event.
+///
+/// One job renders one image line.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Progress {
+    pub total_jobs: usize,
+    pub queued_jobs: usize,
+    pub running_jobs: usize,
+    pub finished_jobs: usize,
+    pub samples_done: usize,
+    pub total_samples: usize,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Returns the finished part of the render in the range `0.0..=1.0`.
+    pub fn fraction(&self) -> f64 {
+        if self.total_samples == 0 {
+            return 1.0;
+        }
+
+        self.samples_done as f64 / self.total_samples as f64
+    }
+
+    /// Estimates the remaining render time from the samples done so far.
+    ///
+    /// Returns `None` as long as no job has finished.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.samples_done == 0 {
+            return None;
+        }
+
+        let remaining = self.total_samples.saturating_sub(self.samples_done);
+        let secs_per_sample = self.elapsed.as_secs_f64() / self.samples_done as f64;
+        Some(Duration::from_secs_f64(secs_per_sample * remaining as f64))
+    }
+}
+
+/// Receives progress events of a render.
+///
+/// All methods are called from the thread that called [`Renderer::render`](crate::Renderer::render),
+/// never from the worker threads.
+pub trait ProgressObserver: Send + Sync {
+    fn job_queued(&self, _progress: &Progress) {}
+    fn job_started(&self, _progress: &Progress) {}
+    fn job_finished(&self, _progress: &Progress) {}
+    fn render_finished(&self, _progress: &Progress) {}
+}
+
+/// Ignores all progress events.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SilentProgress;
+
+impl ProgressObserver for SilentProgress {}
+
+/// Draws a progress bar on stderr, so it does not mix with anything written to stdout.
+#[derive(Debug, Clone, Copy)]
+pub struct TerminalProgress {
+    bar_width: usize,
+}
+
+impl TerminalProgress {
+    pub fn new(bar_width: usize) -> Self {
+        Self { bar_width }
+    }
+
+    fn draw(&self, progress: &Progress) {
+        let filled = (progress.fraction() * self.bar_width as f64) as usize;
+        let eta = match progress.eta() {
+            Some(eta) => format_duration(eta),
+            None => "--:--".to_string(),
+        };
+
+        let mut stderr = std::io::stderr().lock();
+        // a broken terminal is no reason to stop rendering
+        write!(
+            stderr,
+            "\r[{}{}] {:>3.0}% lines {:0>5}/{:0>5}, running {:0>2}, eta {}",
+            "#".repeat(filled.min(self.bar_width)),
+            " ".repeat(self.bar_width.saturating_sub(filled)),
+            100.0 * progress.fraction(),
+            progress.finished_jobs,
+            progress.total_jobs,
+            progress.running_jobs,
+            eta
+        )
+        .ok();
+        stderr.flush().ok();
+    }
+}
+
+impl Default for TerminalProgress {
+    fn default() -> Self {
+        Self::new(40)
+    }
+}
+
+impl ProgressObserver for TerminalProgress {
+    fn job_started(&self, progress: &Progress) {
+        self.draw(progress);
+    }
+
+    fn job_finished(&self, progress: &Progress) {
+        self.draw(progress);
+    }
+
+    fn render_finished(&self, progress: &Progress) {
+        self.draw(progress);
+        eprintln!(" took {}", format_duration(progress.elapsed));
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:0>2}:{:0>2}", secs / 3600, (secs / 60) % 60, secs % 60)
+    } else {
+        format!("{:0>2}:{:0>2}", secs / 60, secs % 60)
+    }
+}
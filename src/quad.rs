@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use crate::{
+    hittable::{HitRecord, HitResult, Hittable},
+    material::Material,
+    ray::Ray,
+    sampler::SampleStream,
+    vec3::{Point3, Vec3},
+};
+
+/// Parallelogram spanned by the edges `u` and `v` starting at the corner `q`.
+pub struct Quad {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    mat: Arc<dyn Material>,
+    normal: Vec3,
+    d: f64,
+    w: Vec3,
+    area: f64,
+}
+
+impl Quad {
+    pub fn new(q: Point3, u: Vec3, v: Vec3, mat: Arc<dyn Material>) -> Self {
+        let n = Vec3::cross(u, v);
+        let normal = Vec3::unit_vector(n);
+
+        Self {
+            q,
+            u,
+            v,
+            mat,
+            normal,
+            d: Vec3::dot(normal, q),
+            w: n / Vec3::dot(n, n),
+            area: n.length(),
+        }
+    }
+
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, r: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> HitResult {
+        let denom = Vec3::dot(self.normal, r.direction());
+
+        // the ray is parallel to the plane
+        if denom.abs() < 1e-8 {
+            return HitResult::Miss;
+        }
+
+        let t = (self.d - Vec3::dot(self.normal, r.origin())) / denom;
+        if t < t_min || t_max < t {
+            return HitResult::Miss;
+        }
+
+        // express the hit point in the coordinates of the edges to see if it lies inside
+        let p = r.at(t);
+        let planar_hit = p - self.q;
+        let alpha = Vec3::dot(self.w, Vec3::cross(planar_hit, self.v));
+        let beta = Vec3::dot(self.w, Vec3::cross(self.u, planar_hit));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return HitResult::Miss;
+        }
+
+        rec.t = t;
+        rec.p = p;
+        rec.set_front_face(r, self.normal);
+        rec.u = alpha;
+        rec.v = beta;
+        rec.object = self as *const Self as usize;
+
+        HitResult::Hit(self.mat.clone())
+    }
+
+    fn sample_direction(&self, origin: Point3, samples: &mut SampleStream) -> Option<Vec3> {
+        let (a, b) = samples.get_2d();
+        let p = self.q + a * self.u + b * self.v;
+
+        Some(p - origin)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let mut rec = HitRecord::default();
+        if self
+            .hit(
+                Ray::new(origin, direction, 0.0),
+                0.001,
+                f64::INFINITY,
+                &mut rec,
+            )
+            .is_miss()
+        {
+            return 0.0;
+        }
+
+        // points are picked uniformly on the area, convert that density to solid angle
+        let distance_squared = rec.t * rec.t * direction.length_squared();
+        let cosine = Vec3::dot(direction, self.normal).abs() / direction.length();
+
+        distance_squared / (cosine * self.area)
+    }
+}
@@ -1,68 +1,162 @@
-use std::{f64::INFINITY, io::Write, sync::mpsc::channel};
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::mpsc::channel,
+    time::Instant,
+};
 
-use rand::{rngs::ThreadRng, thread_rng, Rng};
 use threadpool::ThreadPool;
 use threadpool_scope::scope_with;
 
 use crate::{
+    aov::Aovs,
     camera::Camera,
-    hittable::{HitRecord, HitResult, Hittable, HittableList},
+    cancellation::CancellationToken,
+    denoiser::Denoiser,
+    error::{Error, Result},
+    filter::Filter,
+    framebuffer::Framebuffer,
+    independent_sampler::IndependentSampler,
+    integrator::Integrator,
+    path_tracer::PathTracer,
     ppm_image::PpmImage,
-    ray::Ray,
+    progress::{Progress, ProgressObserver, SilentProgress},
+    sampler::{self, SampleStream, Sampler},
+    scene::Scene,
+    stats::{self, RayCounters, RenderStats},
     utils::clamp,
-    vec3::{Color, Vec3},
+    vec3::Color,
 };
 
+/// Pixels whose filter weights add up to less than this are left black. Filters with
+/// negative lobes can bring the sum close to zero, dividing by it would blow up the noise.
+const MIN_FILTER_WEIGHT: f64 = 1e-3;
+
+/// Weighted sums of the samples splatted into one pixel.
+#[derive(Debug, Clone, Copy, Default)]
+struct Splat {
+    color: Color,
+    aovs: Aovs,
+    weight: f64,
+}
+
+/// Splats of the samples of one image line, which reach into the lines around it.
+struct ImageStrip {
+    /// Index of the first line, counted from the bottom
+    first_line: usize,
+    lines: Vec<Vec<Splat>>,
+    /// Pixels of the image line whose samples were all taken
+    finished_pixels: usize,
+}
+
+pub struct RenderOutput {
+    /// Tone mapped image
+    pub image: PpmImage,
+    /// Tone mapped image after denoising, if the renderer has a [`Denoiser`]
+    pub denoised: Option<PpmImage>,
+    /// Linear color and AOVs of every pixel
+    pub framebuffer: Framebuffer,
+    pub stats: RenderStats,
+}
+
 pub struct Renderer {
-    world: HittableList,
+    scene: Scene,
     target: PpmImage,
+    integrator: Box<dyn Integrator>,
+    sampler: Box<dyn Sampler>,
+    filter: Filter,
+    denoiser: Option<Denoiser>,
+    cancellation: CancellationToken,
+    progress: Box<dyn ProgressObserver>,
 }
 
 impl Renderer {
-    pub fn new(world: HittableList, target: PpmImage) -> Self {
-        Self { world, target }
+    pub fn new(scene: Scene, target: PpmImage) -> Self {
+        Self {
+            scene,
+            target,
+            integrator: Box::new(PathTracer),
+            sampler: Box::new(IndependentSampler),
+            filter: Filter::default(),
+            denoiser: None,
+            cancellation: CancellationToken::new(),
+            progress: Box::new(SilentProgress),
+        }
+    }
+
+    /// Replaces the default [`PathTracer`] with another way to compute the pixel colors.
+    pub fn with_integrator(mut self, integrator: Box<dyn Integrator>) -> Self {
+        self.integrator = integrator;
+        self
+    }
+
+    /// Replaces the default [`IndependentSampler`] that provides the random numbers of
+    /// every pixel sample.
+    pub fn with_sampler(mut self, sampler: Box<dyn Sampler>) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+    /// Replaces the default box [`Filter`] that only counts samples for their own pixel.
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Additionally returns a denoised image, using the AOVs recorded by the integrator.
+    pub fn with_denoiser(mut self, denoiser: Denoiser) -> Self {
+        self.denoiser = Some(denoiser);
+        self
+    }
+
+    /// Reports render progress to `observer`, by default progress is not reported at all.
+    pub fn with_progress(mut self, observer: Box<dyn ProgressObserver>) -> Self {
+        self.progress = observer;
+        self
+    }
+
+    /// Returns a token that stops the render when cancelled.
+    ///
+    /// Pixels that were already finished at that point are still written to the
+    /// returned image, everything else stays black.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
     }
 
     pub fn render(
         mut self,
+        cam: &Camera,
         samples_per_pixel: usize,
         max_depth: usize,
-        aspect_ratio: f64,
-    ) -> PpmImage {
-        // camera
-        // TODO: Make camaera part of the world
-        // TODO: make world into struct with Cam, cam position and list of objects inside
-        let lookfrom = Vec3(13.0, 2.0, 3.0);
-        let lookat = Vec3(0.0, 0.0, 0.0);
-        let vup = Vec3(0.0, 1.0, 0.0);
-        let dist_to_focus = 10.0;
-        let aperture = 0.1;
-
-        let cam = Camera::new(
-            lookfrom,
-            lookat,
-            vup,
-            20.0,
-            aspect_ratio,
-            aperture,
-            dist_to_focus,
-            0.0,
-            1.0,
-        );
+    ) -> Result<RenderOutput> {
+        let start = Instant::now();
 
         // render
-        let mut image_lines = self.calculate_all_pixels(max_depth, &cam);
+        let (splats, counters) = self.calculate_all_pixels(samples_per_pixel, max_depth, cam)?;
 
-        image_lines.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
-        image_lines.reverse();
+        let framebuffer = self.to_framebuffer(splats)?;
+        write_image(&mut self.target, &framebuffer)?;
 
-        let r: Vec<Vec<Color>> = image_lines.iter().map(|elem| elem.1.clone()).collect();
-
-        let final_r = r.into_iter().flatten().collect();
-
-        self.write_colors(samples_per_pixel, final_r);
-
-        self.target
+        let denoised = match &self.denoiser {
+            Some(denoiser) => {
+                let mut image = self.target.clone();
+                write_image(&mut image, &denoiser.denoise(&framebuffer)?)?;
+                Some(image)
+            }
+            None => None,
+        };
+
+        let stats = RenderStats {
+            counters,
+            render: start.elapsed(),
+            ..Default::default()
+        };
+
+        Ok(RenderOutput {
+            image: self.target,
+            denoised,
+            framebuffer,
+            stats,
+        })
     }
 
     fn calculate_single_pixel(
@@ -72,152 +166,246 @@ impl Renderer {
         max_depth: usize,
         samples_per_pixel: usize,
         cam: &Camera,
-        rng: &mut ThreadRng,
-    ) -> Color {
-        let mut pixel_color = Vec3(0.0, 0.0, 0.0);
-        for _ in 0..samples_per_pixel {
-            let r1: f64 = rng.gen();
-            let r2: f64 = rng.gen();
-
-            let u = (i as f64 + r1) / (self.target.width() - 1) as f64;
-            let v = (j as f64 + r2) / (self.target.height() - 1) as f64;
-
-            let r = cam.get_ray(u, v);
-            pixel_color += self.ray_color(r, max_depth);
+        strip: &mut ImageStrip,
+    ) {
+        let seed = sampler::pixel_seed(i, j);
+
+        for s in 0..samples_per_pixel {
+            let mut samples = SampleStream::new(self.sampler.as_ref(), seed, s, samples_per_pixel);
+            let (r1, r2) = samples.get_2d();
+            let (x, y) = (i as f64 + r1, j as f64 + r2);
+
+            let u = x / (self.target.width() - 1) as f64;
+            let v = y / (self.target.height() - 1) as f64;
+
+            let r = cam.get_ray(u, v, &mut samples);
+            stats::count_camera_ray();
+            let mut aovs = Aovs::default();
+            let color =
+                self.integrator
+                    .li_with_aovs(r, &self.scene, max_depth, &mut aovs, &mut samples);
+
+            self.splat(strip, x, y, color, aovs);
         }
-
-        pixel_color
     }
 
-    fn ray_color(&self, r: Ray, depth: usize) -> Color {
-        let mut rec = HitRecord::default();
-
-        // if we exceed the ray bounce limit, no more light is gathered
-        if depth == 0 {
-            return Vec3(0.0, 0.0, 0.0);
-        }
-
-        if let HitResult::Hit(mat) = self.world.hit(r, 0.001, INFINITY, &mut rec) {
-            let mut scattered = Ray::default();
-            let mut attenuation = Color::default();
-
-            if mat.scatter(r, &rec, &mut attenuation, &mut scattered) {
-                return attenuation * self.ray_color(scattered, depth - 1);
-            } else {
-                return Vec3(0.0, 0.0, 0.0);
+    /// Adds a sample at `x` and `y` in pixel coordinates to all pixels of the strip within
+    /// the radius of the filter.
+    fn splat(&self, strip: &mut ImageStrip, x: f64, y: f64, color: Color, aovs: Aovs) {
+        let radius = self.filter.radius();
+        // pixel centers are at half pixels, a sample on the border of the radius only
+        // counts for the pixel on the upper side, so the box filter never counts it twice
+        let pixels = |center: f64, len: usize| {
+            let first = (center - 0.5 - radius).floor() + 1.0;
+            let last = (center - 0.5 + radius).floor();
+            first.clamp(0.0, len as f64) as usize..(last + 1.0).clamp(0.0, len as f64) as usize
+        };
+
+        for py in pixels(y, self.target.height()) {
+            let Some(line) = py
+                .checked_sub(strip.first_line)
+                .and_then(|idx| strip.lines.get_mut(idx))
+            else {
+                continue;
+            };
+
+            for px in pixels(x, self.target.width()) {
+                let weight = self
+                    .filter
+                    .evaluate(px as f64 + 0.5 - x, py as f64 + 0.5 - y);
+                if weight == 0.0 {
+                    continue;
+                }
+
+                let splat = &mut line[px];
+                splat.color += weight * color;
+                splat.aovs += aovs * weight;
+                splat.weight += weight;
             }
         }
-
-        let unit_dir = Vec3::unit_vector(r.direction());
-        let t = 0.5 * (unit_dir.y() + 1.0);
-        (1.0 - t) * Vec3(1.0, 1.0, 1.0) + t * Vec3(0.5, 0.7, 1.0)
     }
 
     fn calculate_single_image_line(
         &self,
         line_idx: usize,
+        samples_per_pixel: usize,
         max_depth: usize,
         cam: &Camera,
-    ) -> (usize, Vec<Color>) {
-        let mut rng = thread_rng();
-        let mut res = Vec::with_capacity(self.target.width());
-
-        for i in 0..self.target.width() {
-            res.insert(
-                i,
-                self.calculate_single_pixel(i, line_idx, max_depth, max_depth, cam, &mut rng),
-            );
+    ) -> ImageStrip {
+        let width = self.target.width();
+        // samples of this line land within the radius around pixel centers in this line
+        let reach = (self.filter.radius() - 0.5).ceil().max(0.0) as usize;
+        let first_line = line_idx.saturating_sub(reach);
+        let last_line = usize::min(line_idx + reach, self.target.height() - 1);
+
+        let mut strip = ImageStrip {
+            first_line,
+            lines: vec![vec![Splat::default(); width]; last_line - first_line + 1],
+            finished_pixels: 0,
+        };
+
+        for i in 0..width {
+            // stop early, but keep the pixels that are already done
+            if self.cancellation.is_cancelled() {
+                break;
+            }
+
+            self.calculate_single_pixel(i, line_idx, max_depth, samples_per_pixel, cam, &mut strip);
+            strip.finished_pixels += 1;
         }
 
-        (line_idx, res)
+        strip
     }
 
-    fn calculate_all_pixels(&self, max_depth: usize, cam: &Camera) -> Vec<(usize, Vec<Color>)> {
+    fn calculate_all_pixels(
+        &self,
+        samples_per_pixel: usize,
+        max_depth: usize,
+        cam: &Camera,
+    ) -> Result<(Vec<Splat>, RayCounters)> {
         enum ThreadStatus {
             Queued,
             Started,
-            Finished,
+            Finished(ImageStrip, RayCounters),
+            Skipped,
         }
 
         let n_workers = 16;
 
         let (status_tx, status_rx) = channel::<ThreadStatus>();
-        let (tx, rx) = channel::<(usize, Vec<Color>)>();
 
         let pool = ThreadPool::new(n_workers);
-        println!("creating jobs for pool");
-
-        scope_with(&pool, |scope| {
-            for j in 0..self.target.height() {
-                let tx = tx.clone();
-                let status_tx = status_tx.clone();
-                status_tx.send(ThreadStatus::Queued).unwrap();
 
-                scope.execute(move || {
-                    status_tx.send(ThreadStatus::Started).unwrap();
-                    let il = self.calculate_single_image_line(j, max_depth, cam);
-
-                    tx.send(il).unwrap();
-                    status_tx.send(ThreadStatus::Finished).unwrap();
+        let start = Instant::now();
+        let mut progress = Progress {
+            total_jobs: self.target.height(),
+            total_samples: self.target.width() * self.target.height() * samples_per_pixel,
+            ..Default::default()
+        };
+        let mut counters = RayCounters::default();
+        let mut splats = vec![Splat::default(); self.target.width() * self.target.height()];
+
+        // the receiver lives until all jobs are done, so failed sends can safely be ignored.
+        // a panicking job is propagated by the scope and turned into an error below.
+        let scope_result = catch_unwind(AssertUnwindSafe(|| {
+            scope_with(&pool, |scope| {
+                for j in 0..self.target.height() {
+                    let status_tx = status_tx.clone();
+                    status_tx.send(ThreadStatus::Queued).ok();
+
+                    scope.execute(move || {
+                        if self.cancellation.is_cancelled() {
+                            status_tx.send(ThreadStatus::Skipped).ok();
+                            return;
+                        }
+
+                        status_tx.send(ThreadStatus::Started).ok();
+
+                        let strip =
+                            self.calculate_single_image_line(j, samples_per_pixel, max_depth, cam);
+                        let counters = stats::take_thread_counters();
+                        status_tx.send(ThreadStatus::Finished(strip, counters)).ok();
+                    });
+                }
+                // drop to not have a deadlock
+                drop(status_tx);
+
+                status_rx.iter().for_each(|v| {
+                    progress.elapsed = start.elapsed();
+
+                    match v {
+                        ThreadStatus::Queued => {
+                            progress.queued_jobs += 1;
+                            self.progress.job_queued(&progress);
+                        }
+                        ThreadStatus::Started => {
+                            progress.queued_jobs -= 1;
+                            progress.running_jobs += 1;
+                            self.progress.job_started(&progress);
+                        }
+                        ThreadStatus::Finished(strip, job_counters) => {
+                            counters += job_counters;
+                            progress.running_jobs -= 1;
+                            progress.finished_jobs += 1;
+                            progress.samples_done += strip.finished_pixels * samples_per_pixel;
+                            // summed up right away, so only the strips in flight are kept
+                            self.add_strip(&mut splats, strip);
+                            self.progress.job_finished(&progress);
+                        }
+                        ThreadStatus::Skipped => progress.queued_jobs -= 1,
+                    };
                 });
-            }
-            println!("done creating jobs");
-
-            // drop to not have a deadlock
-            drop(tx);
-            drop(status_tx);
-
-            let mut remaining_jobs = 0;
-            let mut running_jobs = 0;
-            let mut finished_jobs = 0;
-
-            status_rx.iter().for_each(|v| {
-                match v {
-                    ThreadStatus::Queued => remaining_jobs += 1,
-                    ThreadStatus::Started => {
-                        remaining_jobs -= 1;
-                        running_jobs += 1
-                    }
-                    ThreadStatus::Finished => {
-                        running_jobs -= 1;
-                        finished_jobs += 1
-                    }
-                };
-                print!(
-                    "\rJobs remaining/running/finished: {:0>5}/{:0>5}/{:0>5}",
-                    remaining_jobs, running_jobs, finished_jobs
-                );
-                std::io::stdout().flush().unwrap();
             });
-        });
+        }));
 
         pool.join();
 
-        return rx.iter().collect();
+        progress.elapsed = start.elapsed();
+        self.progress.render_finished(&progress);
+
+        if scope_result.is_err() {
+            return Err(Error::WorkerPanicked);
+        }
+
+        Ok((splats, counters))
+    }
+
+    /// Adds the splats of `strip` to the ones of the whole image.
+    fn add_strip(&self, splats: &mut [Splat], strip: ImageStrip) {
+        let width = self.target.width();
+        let height = self.target.height();
+
+        // lines are counted from the bottom, but the image is stored top to bottom
+        for (k, line) in strip.lines.into_iter().enumerate() {
+            let offset = (height - 1 - (strip.first_line + k)) * width;
+
+            for (i, splat) in line.into_iter().enumerate() {
+                let sum = &mut splats[offset + i];
+                sum.color += splat.color;
+                sum.aovs += splat.aovs;
+                sum.weight += splat.weight;
+            }
+        }
     }
 
-    fn write_colors(&mut self, samples_per_pixel: usize, pixels: Vec<Color>) {
-        for (idx, pixel_color) in pixels.into_iter().enumerate() {
-            self.write_color(pixel_color, samples_per_pixel, idx);
+    /// Normalizes the summed up splats of the image into a framebuffer.
+    fn to_framebuffer(&self, splats: Vec<Splat>) -> Result<Framebuffer> {
+        let mut framebuffer = Framebuffer::new(self.target.width(), self.target.height());
+        for (idx, splat) in splats.into_iter().enumerate() {
+            // pixels without samples, e.g. after cancelling, stay black
+            if splat.weight > MIN_FILTER_WEIGHT {
+                // negative filter lobes can overshoot below zero next to bright edges
+                let scale = 1.0 / splat.weight;
+                framebuffer.set_pixel(
+                    idx,
+                    (scale * splat.color).max_zero(),
+                    (splat.aovs * scale).max_zero(),
+                )?;
+            }
         }
+
+        Ok(framebuffer)
     }
+}
 
-    pub fn write_color(&mut self, pixel_color: Color, samples_per_pixel: usize, idx: usize) {
-        let (mut r, mut g, mut b) = (pixel_color.x(), pixel_color.y(), pixel_color.z());
-
-        // divide the color by the number of samples and gamma correct for gamma=2.0
-        let scale = 1.0 / samples_per_pixel as f64;
-        r = (scale * r).sqrt();
-        g = (scale * g).sqrt();
-        b = (scale * b).sqrt();
-
-        self.target.set_value(
-            idx,
-            (
-                (256.0 * clamp(r, 0.0, 0.999)) as u8,
-                (256.0 * clamp(g, 0.0, 0.999)) as u8,
-                (256.0 * clamp(b, 0.0, 0.999)) as u8,
-            ),
-        );
+/// Tone maps the linear colors of `framebuffer` into `image`.
+fn write_image(image: &mut PpmImage, framebuffer: &Framebuffer) -> Result<()> {
+    for idx in 0..framebuffer.width() * framebuffer.height() {
+        image.set_value(idx, to_rgb8(framebuffer.color(idx)))?;
     }
+
+    Ok(())
+}
+
+fn to_rgb8(pixel_color: Color) -> (u8, u8, u8) {
+    // gamma correct for gamma=2.0
+    let r = pixel_color.x().sqrt();
+    let g = pixel_color.y().sqrt();
+    let b = pixel_color.z().sqrt();
+
+    (
+        (256.0 * clamp(r, 0.0, 0.999)) as u8,
+        (256.0 * clamp(g, 0.0, 0.999)) as u8,
+        (256.0 * clamp(b, 0.0, 0.999)) as u8,
+    )
 }
@@ -0,0 +1,316 @@
+use crate::{
+    dielectric::{absorption_for_color, transmittance},
+    fresnel,
+    hittable::HitRecord,
+    material::{local_directions, Material, ScatterRecord},
+    microfacet::TrowbridgeReitz,
+    onb::Onb,
+    ray::Ray,
+    sampler::SampleStream,
+    vec3::{Color, Vec3},
+};
+
+/// Frosted glass made of GGX microfacets that each reflect or refract like [`crate::Dielectric`].
+///
+/// Light passing through is blurred more the higher the roughness, with a roughness of
+/// zero it is smooth glass. Like the smooth [`crate::Dielectric`] it is clear unless it
+/// absorbs some of the light passing through it.
+///
+/// Thin-walled glass, e.g. a window pane modelled as a single quad, lets the light pass
+/// straight through instead of bending it, as if it entered and left a thin slab.
+#[derive(Debug, Clone, Copy)]
+pub struct RoughDielectric {
+    ir: f64,           // index of refraction
+    absorption: Color, // per unit of distance traveled inside
+    distribution: TrowbridgeReitz,
+    thin_walled: bool,
+}
+
+impl RoughDielectric {
+    pub fn new(ir: f64, roughness: f64) -> Self {
+        Self {
+            ir,
+            absorption: Color::default(),
+            distribution: TrowbridgeReitz::new(roughness),
+            thin_walled: false,
+        }
+    }
+
+    /// Treats the surface as the two sides of a thin slab instead of the boundary of a
+    /// solid. Thin walls have no inside, so they don't absorb any light.
+    pub fn with_thin_walled(mut self, thin_walled: bool) -> Self {
+        self.thin_walled = thin_walled;
+        self
+    }
+
+    /// See [`crate::Dielectric::with_absorption`].
+    pub fn with_absorption(mut self, absorption: Color) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// See [`crate::Dielectric::with_color_at_distance`].
+    pub fn with_color_at_distance(mut self, color: Color, distance: f64) -> Self {
+        self.absorption = absorption_for_color(color, distance);
+        self
+    }
+
+    /// Ratio of the index of refraction behind the surface to the one in front of it.
+    fn eta(&self, rec: &HitRecord) -> f64 {
+        if rec.front_face() || self.thin_walled {
+            self.ir
+        } else {
+            1.0 / self.ir
+        }
+    }
+
+    /// Fraction of the light reflected by a microfacet, for a thin wall including the light
+    /// reflected back and forth inside before leaving on the side it arrived from.
+    fn reflectance(&self, cos_theta: f64, eta: f64) -> f64 {
+        let r = fresnel::dielectric(cos_theta, eta);
+        if self.thin_walled && r < 1.0 {
+            let t = 1.0 - r;
+            return r + t * t * r / (1.0 - r * r);
+        }
+        r
+    }
+
+    /// Part of the light that is left after reaching the surface.
+    fn transmittance(&self, r_in: Ray, rec: &HitRecord) -> Color {
+        if self.thin_walled {
+            return Vec3(1.0, 1.0, 1.0);
+        }
+        transmittance(self.absorption, r_in, rec)
+    }
+
+    /// Value of the BSDF and density of picking `wi` for a thin wall, where the light passing
+    /// through continues in the mirror image of the reflected direction.
+    fn thin_walled_bsdf_pdf(&self, wo: Vec3, wi: Vec3) -> (f64, f64) {
+        let reflected = Vec3(wi.x(), wi.y(), wi.z().abs());
+        let h = match Self::half_vector(wo, reflected, self.ir) {
+            Some(h) if !self.is_specular() && wi.z() != 0.0 => h,
+            _ => return (0.0, 0.0),
+        };
+
+        let cos_o = Vec3::dot(wo, h);
+        let reflectance = self.reflectance(cos_o, self.ir);
+        let share = if wi.z() > 0.0 {
+            reflectance
+        } else {
+            1.0 - reflectance
+        };
+
+        let bsdf = share * self.distribution.d(h) * self.distribution.g(wo, reflected)
+            / (4.0 * wo.z() * reflected.z());
+        let pdf = share * self.distribution.visible_normal_pdf(wo, h) / (4.0 * cos_o);
+        (bsdf, pdf)
+    }
+
+    /// Microfacet normal that turns `wo` into `wi`, pointing above the surface, or `None`
+    /// if no microfacet can do that.
+    fn half_vector(wo: Vec3, wi: Vec3, eta: f64) -> Option<Vec3> {
+        let reflect = wi.z() > 0.0;
+        let h = if reflect { wo + wi } else { wo + eta * wi };
+        if h.near_zero() {
+            return None;
+        }
+
+        let h = Vec3::unit_vector(h);
+        let h = if h.z() < 0.0 { -h } else { h };
+
+        // both directions have to be on the correct side of the microfacet
+        let (cos_o, cos_i) = (Vec3::dot(wo, h), Vec3::dot(wi, h));
+        if cos_o <= 0.0 || (reflect && cos_i <= 0.0) || (!reflect && cos_i >= 0.0) {
+            return None;
+        }
+
+        Some(h)
+    }
+}
+
+impl Material for RoughDielectric {
+    fn scatter(
+        &self,
+        r_in: Ray,
+        rec: &HitRecord,
+        samples: &mut SampleStream,
+    ) -> Option<ScatterRecord> {
+        let basis = Onb::from_w(rec.normal);
+        let wo = basis.to_local(-Vec3::unit_vector(r_in.direction()));
+        if wo.z() <= 0.0 {
+            return None;
+        }
+
+        let eta = self.eta(rec);
+        let h = if self.distribution.is_smooth() {
+            Vec3(0.0, 0.0, 1.0)
+        } else {
+            self.distribution
+                .sample_visible_normal(wo, samples.get_2d())
+        };
+
+        let cos_theta = Vec3::dot(wo, h);
+        let reflect = self.reflectance(cos_theta, eta) > samples.get_1d();
+        let wi = if reflect {
+            Vec3::reflect(-wo, h)
+        } else if self.thin_walled {
+            let reflected = Vec3::reflect(-wo, h);
+            Vec3(reflected.x(), reflected.y(), -reflected.z())
+        } else {
+            Vec3::refract(-wo, h, 1.0 / eta)
+        };
+
+        // microfacets facing away can send the light to the wrong side of the surface
+        if (wi.z() > 0.0) != reflect {
+            return None;
+        }
+
+        if self.distribution.is_smooth() {
+            return Some(ScatterRecord {
+                direction: basis.local(Vec3::unit_vector(wi)),
+                bsdf: self.transmittance(r_in, rec),
+                pdf: 1.0,
+                specular: true,
+            });
+        }
+
+        let direction = basis.local(Vec3::unit_vector(wi));
+        let pdf = self.pdf(r_in, rec, direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        Some(ScatterRecord {
+            direction,
+            bsdf: self.bsdf(r_in, rec, direction),
+            pdf,
+            specular: false,
+        })
+    }
+
+    fn is_specular(&self) -> bool {
+        self.distribution.is_smooth()
+    }
+
+    fn bsdf(&self, r_in: Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        let (wo, wi) = local_directions(r_in, rec, direction);
+        if self.thin_walled {
+            let (bsdf, _) = self.thin_walled_bsdf_pdf(wo, wi);
+            return Vec3(bsdf, bsdf, bsdf);
+        }
+
+        let eta = self.eta(rec);
+        let h = match Self::half_vector(wo, wi, eta) {
+            Some(h) if !self.is_specular() && wi.z() != 0.0 => h,
+            _ => return Color::default(),
+        };
+
+        let (cos_o, cos_i) = (Vec3::dot(wo, h), Vec3::dot(wi, h));
+        let fresnel = fresnel::dielectric(cos_o, eta);
+        let dg = self.distribution.d(h) * self.distribution.g(wo, wi);
+
+        let value = if wi.z() > 0.0 {
+            fresnel * dg / (4.0 * wo.z() * wi.z())
+        } else {
+            // scaled by eta^2 to not darken the light that passes through, which the
+            // smooth dielectric doesn't either
+            let denominator = (cos_o + eta * cos_i).powi(2);
+            eta * eta * (1.0 - fresnel) * dg * (cos_o * cos_i).abs()
+                / (wo.z() * wi.z().abs() * denominator)
+        };
+
+        value * transmittance(self.absorption, r_in, rec)
+    }
+
+    fn pdf(&self, r_in: Ray, rec: &HitRecord, direction: Vec3) -> f64 {
+        let (wo, wi) = local_directions(r_in, rec, direction);
+        if self.thin_walled {
+            let (_, pdf) = self.thin_walled_bsdf_pdf(wo, wi);
+            return pdf;
+        }
+
+        let eta = self.eta(rec);
+        let h = match Self::half_vector(wo, wi, eta) {
+            Some(h) if !self.is_specular() => h,
+            _ => return 0.0,
+        };
+
+        let (cos_o, cos_i) = (Vec3::dot(wo, h), Vec3::dot(wi, h));
+        let fresnel = fresnel::dielectric(cos_o, eta);
+        let pdf_h = self.distribution.visible_normal_pdf(wo, h);
+
+        // density of the microfacet normal, converted to the density of the direction it
+        // reflects or refracts into
+        if wi.z() > 0.0 {
+            fresnel * pdf_h / (4.0 * cos_o)
+        } else {
+            let denominator = (cos_o + eta * cos_i).powi(2);
+            (1.0 - fresnel) * pdf_h * eta * eta * cos_i.abs() / denominator
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::checks;
+
+    #[test]
+    fn pdf_integrates_to_at_most_one() {
+        for roughness in [0.6, 0.8, 1.0] {
+            for thin_walled in [false, true] {
+                for wo in checks::VIEW_DIRECTIONS
+                    .into_iter()
+                    .chain(checks::INSIDE_DIRECTIONS)
+                {
+                    let (r_in, rec) = checks::hit(wo);
+                    let glass = RoughDielectric::new(1.5, roughness).with_thin_walled(thin_walled);
+                    let integral = checks::pdf_integral(&glass, r_in, &rec);
+                    assert!(integral <= 1.01, "{roughness}, {wo:?}: {integral}");
+                    assert!(integral > 0.4, "{roughness}, {wo:?}: {integral}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn scatter_matches_bsdf() {
+        for roughness in [0.6, 0.8, 1.0] {
+            for thin_walled in [false, true] {
+                for wo in checks::VIEW_DIRECTIONS
+                    .into_iter()
+                    .chain(checks::INSIDE_DIRECTIONS)
+                {
+                    let (r_in, rec) = checks::hit(wo);
+                    let glass = RoughDielectric::new(1.5, roughness).with_thin_walled(thin_walled);
+                    let (sampled, uniform) = checks::albedo_estimates(&glass, r_in, &rec);
+                    assert!(
+                        (sampled - uniform).abs() < 0.02,
+                        "{roughness}, {wo:?}: {sampled} != {uniform}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn smooth_thin_wall_does_not_bend_light() {
+        let glass = RoughDielectric::new(1.5, 0.0).with_thin_walled(true);
+        let wo = Vec3(0.0, 0.6, 0.8);
+        let (r_in, rec) = checks::hit(wo);
+
+        let sampler = crate::independent_sampler::IndependentSampler;
+        let mut transmitted = 0;
+        for index in 0..1000 {
+            let mut samples = SampleStream::new(&sampler, 1, index, 1000);
+            samples.start_bounce(0);
+            let scatter = glass.scatter(r_in, &rec, &mut samples).unwrap();
+            if scatter.direction.z() < 0.0 {
+                assert!((scatter.direction + wo).near_zero());
+                transmitted += 1;
+            }
+        }
+        // a slab of glass reflects about 9% at this angle
+        assert!((850..950).contains(&transmitted), "{transmitted}");
+    }
+}
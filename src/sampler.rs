@@ -0,0 +1,197 @@
+use rand::{thread_rng, Rng};
+
+/// Produces the random numbers of every pixel sample, e.g. well distributed low
+/// discrepancy sequences that converge faster than independent random numbers.
+///
+/// The numbers of one pixel sample form a point in a space with one dimension per number
+/// drawn. The renderer gives every use a fixed dimension, see [`SampleStream`].
+pub trait Sampler: Send + Sync {
+    /// Number in `[0, 1)` for `dimension` of sample `index` out of `count` samples taken in
+    /// the pixel identified by `seed`.
+    fn get_1d(&self, seed: u64, index: usize, count: usize, dimension: usize) -> f64;
+
+    /// Numbers for `dimension` and `dimension + 1`, samplers that are stratified in two
+    /// dimensions override this.
+    fn get_2d(&self, seed: u64, index: usize, count: usize, dimension: usize) -> (f64, f64) {
+        (
+            self.get_1d(seed, index, count, dimension),
+            self.get_1d(seed, index, count, dimension + 1),
+        )
+    }
+}
+
+/// Dimensions of the camera ray: position in the pixel, position on the lens and time
+const CAMERA_DIMENSIONS: usize = 5;
+
+/// Dimensions reserved for every bounce of a path
+const BOUNCE_DIMENSIONS: usize = 16;
+
+/// The numbers of a single pixel sample, drawn one dimension after the other.
+///
+/// The camera uses the first dimensions, then every bounce starts at its own fixed
+/// dimension, so the numbers of a bounce never depend on how many the bounces before it
+/// used. Numbers drawn beyond the dimensions of a bounce are independent random numbers.
+pub struct SampleStream<'a> {
+    sampler: &'a dyn Sampler,
+    seed: u64,
+    index: usize,
+    count: usize,
+    dimension: usize,
+    end: usize,
+}
+
+impl<'a> SampleStream<'a> {
+    pub fn new(sampler: &'a dyn Sampler, seed: u64, index: usize, count: usize) -> Self {
+        Self {
+            sampler,
+            seed,
+            index,
+            count,
+            dimension: 0,
+            end: CAMERA_DIMENSIONS,
+        }
+    }
+
+    /// Moves to the dimensions of bounce `depth`, with 0 being the first hit.
+    pub fn start_bounce(&mut self, depth: usize) {
+        self.dimension = CAMERA_DIMENSIONS + depth * BOUNCE_DIMENSIONS;
+        self.end = self.dimension + BOUNCE_DIMENSIONS;
+    }
+
+    pub fn get_1d(&mut self) -> f64 {
+        if self.dimension >= self.end {
+            return thread_rng().gen();
+        }
+
+        let value = self
+            .sampler
+            .get_1d(self.seed, self.index, self.count, self.dimension);
+        self.dimension += 1;
+        value
+    }
+
+    pub fn get_2d(&mut self) -> (f64, f64) {
+        if self.dimension + 1 >= self.end {
+            return (thread_rng().gen(), thread_rng().gen());
+        }
+
+        let value = self
+            .sampler
+            .get_2d(self.seed, self.index, self.count, self.dimension);
+        self.dimension += 2;
+        value
+    }
+}
+
+/// Seed of the pixel in column `i` and line `j`, that samplers use to decorrelate pixels.
+///
+/// The seed holds the coordinates themselves, samplers that want random looking seeds hash
+/// it, samplers that arrange their error over the image get the pixel back with
+/// [`pixel_coordinates`].
+pub(crate) fn pixel_seed(i: usize, j: usize) -> u64 {
+    ((j as u64) << 32) | (i as u64 & 0xffff_ffff)
+}
+
+/// Column and line of the pixel a seed from [`pixel_seed`] belongs to.
+pub(crate) fn pixel_coordinates(seed: u64) -> (usize, usize) {
+    ((seed & 0xffff_ffff) as usize, (seed >> 32) as usize)
+}
+
+/// splitmix64 finalizer, spreads similar inputs over the whole range.
+pub(crate) fn hash(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// Hashes `value` together with `seed`.
+pub(crate) fn mix(seed: u64, value: u64) -> u64 {
+    hash(seed ^ hash(value.wrapping_add(0x9e3779b97f4a7c15)))
+}
+
+/// Maps the upper 53 bits of `x` to a number in `[0, 1)`.
+pub(crate) fn to_unit(x: u64) -> f64 {
+    (x >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+}
+
+#[cfg(test)]
+pub(crate) mod checks {
+    use super::*;
+
+    /// Whether the first `count` samples of the pixel put one value of `dimension` into
+    /// every stratum of width `1 / count`.
+    pub(crate) fn covers_strata(
+        sampler: &dyn Sampler,
+        seed: u64,
+        count: usize,
+        dimension: usize,
+    ) -> bool {
+        let mut hits = vec![0; count];
+        for index in 0..count {
+            let value = sampler.get_1d(seed, index, count, dimension);
+            hits[(value * count as f64) as usize] += 1;
+        }
+        hits.iter().all(|&hits| hits == 1)
+    }
+
+    /// Whether the first `nx * ny` samples of the pixel put one pair of values of
+    /// `dimension` and `dimension + 1` into every cell of an `nx` by `ny` grid.
+    pub(crate) fn covers_grid(
+        sampler: &dyn Sampler,
+        seed: u64,
+        (nx, ny): (usize, usize),
+        dimension: usize,
+    ) -> bool {
+        let count = nx * ny;
+        let mut hits = vec![0; count];
+        for index in 0..count {
+            let (u, v) = sampler.get_2d(seed, index, count, dimension);
+            hits[(v * ny as f64) as usize * nx + (u * nx as f64) as usize] += 1;
+        }
+        hits.iter().all(|&hits| hits == 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        blue_noise_sampler::BlueNoiseSampler, halton_sampler::HaltonSampler,
+        independent_sampler::IndependentSampler, sobol_sampler::SobolSampler,
+        stratified_sampler::StratifiedSampler,
+    };
+
+    #[test]
+    fn samples_lie_in_unit_interval() {
+        let samplers: [&dyn Sampler; 5] = [
+            &IndependentSampler,
+            &StratifiedSampler,
+            &HaltonSampler,
+            &SobolSampler,
+            &BlueNoiseSampler,
+        ];
+        for (s, sampler) in samplers.into_iter().enumerate() {
+            for seed in [pixel_seed(0, 0), pixel_seed(17, 3), pixel_seed(4095, 2160)] {
+                for count in [1, 7, 64] {
+                    for index in 0..count {
+                        // beyond the dimensions of the tables the samplers fall back
+                        for dimension in 0..40 {
+                            let value = sampler.get_1d(seed, index, count, dimension);
+                            assert!((0.0..1.0).contains(&value), "{s}, {dimension}: {value}");
+                            let (u, v) = sampler.get_2d(seed, index, count, dimension);
+                            assert!((0.0..1.0).contains(&u), "{s}, {dimension}: {u}");
+                            assert!((0.0..1.0).contains(&v), "{s}, {dimension}: {v}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pixel_seed_round_trips() {
+        for (i, j) in [(0, 0), (17, 3), (4095, 2160)] {
+            assert_eq!(pixel_coordinates(pixel_seed(i, j)), (i, j));
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use crate::{
+    hittable::{Hittable, HittableList},
+    light::Light,
+    ray::Ray,
+    sky::PreethamSky,
+    vec3::{Color, Point3, Vec3},
+};
+
+/// What a ray sees when it leaves the scene without hitting anything.
+#[derive(Debug, Clone)]
+pub enum Background {
+    /// Blue to white gradient
+    Sky,
+    Solid(Color),
+    /// Daylight sky, usually paired with its [`PreethamSky::sun`] as a light
+    Physical(Box<PreethamSky>),
+}
+
+impl Background {
+    pub fn color(&self, r: Ray) -> Color {
+        match self {
+            Background::Sky => {
+                let unit_dir = Vec3::unit_vector(r.direction());
+                let t = 0.5 * (unit_dir.y() + 1.0);
+                (1.0 - t) * Vec3(1.0, 1.0, 1.0) + t * Vec3(0.5, 0.7, 1.0)
+            }
+            Background::Solid(color) => *color,
+            Background::Physical(sky) => sky.radiance(r.direction()),
+        }
+    }
+}
+
+/// Objects to render together with the lights that are sampled directly.
+pub struct Scene {
+    world: HittableList,
+    area_lights: Vec<Arc<dyn Hittable>>,
+    lights: Vec<Arc<dyn Light>>,
+    background: Background,
+}
+
+impl Scene {
+    pub fn new(world: HittableList) -> Self {
+        Self {
+            world,
+            area_lights: Vec::new(),
+            lights: Vec::new(),
+            background: Background::Sky,
+        }
+    }
+
+    pub fn add(&mut self, object: Arc<dyn Hittable>) {
+        self.world.add(object);
+    }
+
+    /// Adds an emissive object that is sampled directly when shading diffuse surfaces.
+    ///
+    /// The object has to support [`Hittable::sample_direction`]. Emitters that are only added
+    /// with [`Scene::add`] still light the scene, but converge a lot slower.
+    pub fn add_area_light(&mut self, object: Arc<dyn Hittable>) {
+        self.world.add(object.clone());
+        self.area_lights.push(object);
+    }
+
+    /// Adds a light that is not part of the geometry, e.g. a point light or the sun.
+    pub fn add_light(&mut self, light: Arc<dyn Light>) {
+        self.lights.push(light);
+    }
+
+    pub fn set_background(&mut self, background: Background) {
+        self.background = background;
+    }
+
+    pub fn world(&self) -> &HittableList {
+        &self.world
+    }
+
+    pub fn area_lights(&self) -> &[Arc<dyn Hittable>] {
+        &self.area_lights
+    }
+
+    pub fn lights(&self) -> &[Arc<dyn Light>] {
+        &self.lights
+    }
+
+    pub fn background(&self) -> &Background {
+        &self.background
+    }
+
+    /// Density (per solid angle) with which the renderer picks `direction` at `origin` when
+    /// sampling a random area light.
+    pub fn area_light_pdf(&self, origin: Point3, direction: Vec3) -> f64 {
+        if self.area_lights.is_empty() {
+            return 0.0;
+        }
+
+        let pdf_sum: f64 = self
+            .area_lights
+            .iter()
+            .map(|light| light.pdf_value(origin, direction))
+            .sum();
+
+        pdf_sum / self.area_lights.len() as f64
+    }
+}
+
+impl From<HittableList> for Scene {
+    fn from(world: HittableList) -> Self {
+        Self::new(world)
+    }
+}
@@ -0,0 +1,213 @@
+use std::f64::consts::PI;
+
+use crate::{
+    directional_light::DirectionalLight,
+    utils::clamp,
+    vec3::{Color, Vec3},
+};
+
+/// Angular diameter of the sun as seen from earth, in degrees.
+const SUN_ANGULAR_DIAMETER: f64 = 0.53;
+
+/// Illuminance of the sun outside the atmosphere, in kcd/m^2 * sr like the sky luminance.
+const SUN_ILLUMINANCE: f64 = 128.0;
+
+/// Maps the luminance of the model (kcd/m^2) to the radiance used by the renderer, so a
+/// white diffuse surface in the midday sun ends up close to white.
+const LUMINANCE_SCALE: f64 = 1.0 / 25.0;
+
+/// Coefficients of the Perez sky luminance distribution.
+#[derive(Debug, Clone, Copy)]
+struct Perez {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+}
+
+impl Perez {
+    /// `cos_theta` is the cosine of the view zenith angle, `gamma` the angle between view and sun.
+    fn eval(&self, cos_theta: f64, gamma: f64) -> f64 {
+        let cos_gamma = gamma.cos();
+        (1.0 + self.a * (self.b / cos_theta).exp())
+            * (1.0 + self.c * (self.d * gamma).exp() + self.e * cos_gamma * cos_gamma)
+    }
+}
+
+/// Analytic daylight sky after Preetham, Shirley and Smits, "A Practical Analytic Model for
+/// Daylight" (1999).
+///
+/// The sun disc itself is not part of the sky, add [`PreethamSky::sun`] to the scene lights.
+/// The model is only meant for a sun above the horizon.
+#[derive(Debug, Clone, Copy)]
+pub struct PreethamSky {
+    to_sun: Vec3,
+    turbidity: f64,
+    ground_albedo: Color,
+    theta_sun: f64,
+    zenith: (f64, f64, f64), // Y, x, y
+    perez: [Perez; 3],       // Y, x, y
+    ground_radiance: Color,
+}
+
+impl PreethamSky {
+    /// `sun_direction` points from the scene towards the sun, `turbidity` describes the haze
+    /// in the air from 2 (very clear) to 10 (hazy), `ground_albedo` is the color of the
+    /// ground below the horizon.
+    pub fn new(sun_direction: Vec3, turbidity: f64, ground_albedo: Color) -> Self {
+        let to_sun = Vec3::unit_vector(sun_direction);
+        let t = clamp(turbidity, 1.7, 10.0);
+        let theta_sun = clamp(to_sun.y(), 0.0, 1.0).acos();
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_sun);
+        let zenith_luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+
+        let (th, th2, th3) = (theta_sun, theta_sun * theta_sun, theta_sun.powi(3));
+        let zenith_x = t * t * (0.00166 * th3 - 0.00375 * th2 + 0.00209 * th)
+            + t * (-0.02903 * th3 + 0.06377 * th2 - 0.03202 * th + 0.00394)
+            + (0.11693 * th3 - 0.21196 * th2 + 0.06052 * th + 0.25886);
+        let zenith_y = t * t * (0.00275 * th3 - 0.00610 * th2 + 0.00317 * th)
+            + t * (-0.04214 * th3 + 0.08970 * th2 - 0.04153 * th + 0.00516)
+            + (0.15346 * th3 - 0.26756 * th2 + 0.06670 * th + 0.26688);
+
+        let perez = [
+            Perez {
+                a: 0.1787 * t - 1.4630,
+                b: -0.3554 * t + 0.4275,
+                c: -0.0227 * t + 5.3251,
+                d: 0.1206 * t - 2.5771,
+                e: -0.0670 * t + 0.3703,
+            },
+            Perez {
+                a: -0.0193 * t - 0.2592,
+                b: -0.0665 * t + 0.0008,
+                c: -0.0004 * t + 0.2125,
+                d: -0.0641 * t - 0.8989,
+                e: -0.0033 * t + 0.0452,
+            },
+            Perez {
+                a: -0.0167 * t - 0.2608,
+                b: -0.0950 * t + 0.0092,
+                c: -0.0079 * t + 0.2102,
+                d: -0.0441 * t - 1.6537,
+                e: -0.0109 * t + 0.0529,
+            },
+        ];
+
+        let mut sky = Self {
+            to_sun,
+            turbidity: t,
+            ground_albedo,
+            theta_sun,
+            zenith: (zenith_luminance, zenith_x, zenith_y),
+            perez,
+            ground_radiance: Color::default(),
+        };
+        sky.ground_radiance = sky.ground_albedo * sky.ground_irradiance() / PI;
+        sky
+    }
+
+    /// Light seen when looking into `direction`.
+    pub fn radiance(&self, direction: Vec3) -> Color {
+        let direction = Vec3::unit_vector(direction);
+        if direction.y() < 0.0 {
+            return self.ground_radiance;
+        }
+
+        self.sky_radiance(direction)
+    }
+
+    /// The sun matching this sky, reddened by the atmosphere it shines through.
+    pub fn sun(&self) -> DirectionalLight {
+        DirectionalLight::new(-self.to_sun, self.sun_irradiance(), SUN_ANGULAR_DIAMETER)
+    }
+
+    pub fn sun_direction(&self) -> Vec3 {
+        self.to_sun
+    }
+
+    pub fn turbidity(&self) -> f64 {
+        self.turbidity
+    }
+
+    fn sky_radiance(&self, direction: Vec3) -> Color {
+        // keep the horizon finite
+        let cos_theta = f64::max(direction.y(), 0.01);
+        let gamma = clamp(Vec3::dot(direction, self.to_sun), -1.0, 1.0).acos();
+
+        let relative =
+            |perez: &Perez| perez.eval(cos_theta, gamma) / perez.eval(1.0, self.theta_sun);
+        let luminance = self.zenith.0 * relative(&self.perez[0]);
+        let x = self.zenith.1 * relative(&self.perez[1]);
+        let y = self.zenith.2 * relative(&self.perez[2]);
+
+        LUMINANCE_SCALE * xyy_to_rgb(x, y, luminance)
+    }
+
+    /// Spectral transmittance of the atmosphere towards the sun, sampled at the wavelengths
+    /// of red, green and blue, using the Rayleigh and aerosol terms from the appendix of the paper.
+    fn sun_transmittance(&self) -> Color {
+        let theta_deg = self.theta_sun.to_degrees();
+        let air_mass =
+            1.0 / (self.theta_sun.cos() + 0.15 * (93.885 - theta_deg).max(0.0).powf(-1.253));
+
+        let beta = 0.04608 * self.turbidity - 0.04586;
+        let transmittance = |lambda: f64| {
+            let rayleigh = 0.008735 * lambda.powf(-4.08);
+            let aerosol = beta * lambda.powf(-1.3);
+            (-air_mass * (rayleigh + aerosol)).exp()
+        };
+
+        // wavelengths in micrometers
+        Vec3(
+            transmittance(0.65),
+            transmittance(0.57),
+            transmittance(0.475),
+        )
+    }
+
+    fn sun_irradiance(&self) -> Color {
+        LUMINANCE_SCALE * SUN_ILLUMINANCE * self.sun_transmittance()
+    }
+
+    /// Light arriving on the ground from sky and sun, integrated numerically over the hemisphere.
+    fn ground_irradiance(&self) -> Color {
+        let (n_theta, n_phi) = (16, 32);
+        let d_theta = 0.5 * PI / n_theta as f64;
+        let d_phi = 2.0 * PI / n_phi as f64;
+
+        let mut irradiance = Color::default();
+        for i in 0..n_theta {
+            let theta = (i as f64 + 0.5) * d_theta;
+            for j in 0..n_phi {
+                let phi = (j as f64 + 0.5) * d_phi;
+                let direction = Vec3(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                irradiance +=
+                    (theta.cos() * theta.sin() * d_theta * d_phi) * self.sky_radiance(direction);
+            }
+        }
+
+        irradiance + self.to_sun.y().max(0.0) * self.sun_irradiance()
+    }
+}
+
+/// Converts a CIE xyY color to linear sRGB.
+fn xyy_to_rgb(x: f64, y: f64, luminance: f64) -> Color {
+    if y <= 0.0 {
+        return Color::default();
+    }
+
+    let big_x = x / y * luminance;
+    let big_z = (1.0 - x - y) / y * luminance;
+
+    Vec3(
+        f64::max(3.2406 * big_x - 1.5372 * luminance - 0.4986 * big_z, 0.0),
+        f64::max(-0.9689 * big_x + 1.8758 * luminance + 0.0415 * big_z, 0.0),
+        f64::max(0.0557 * big_x - 0.2040 * luminance + 1.0570 * big_z, 0.0),
+    )
+}
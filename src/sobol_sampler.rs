@@ -0,0 +1,103 @@
+use crate::sampler::{mix, Sampler};
+
+/// Sobol sequence, padded from its first two dimensions and Owen scrambled.
+///
+/// Every pair of dimensions uses the well distributed first two dimensions of the
+/// sequence, with its own scrambling and shuffled sample order, after Burley, "Practical
+/// Hash-based Owen Scrambling" (2020). Works best with a power of two samples per pixel.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SobolSampler;
+
+impl SobolSampler {
+    /// Index of the sample in the sequence, shuffled per pixel and pair of dimensions.
+    fn shuffled_index(seed: u64, index: usize, dimension: usize) -> u32 {
+        let pair_seed = mix(seed, (dimension / 2) as u64);
+        nested_uniform_scramble(index as u32, pair_seed as u32)
+    }
+
+    fn scrambled(value: u32, seed: u64, dimension: usize) -> f64 {
+        let scrambled = nested_uniform_scramble(value, mix(seed, dimension as u64) as u32);
+        scrambled as f64 * (1.0 / (1u64 << 32) as f64)
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn get_1d(&self, seed: u64, index: usize, _count: usize, dimension: usize) -> f64 {
+        let index = Self::shuffled_index(seed, index, dimension);
+        Self::scrambled(sobol_0(index), seed, dimension)
+    }
+
+    fn get_2d(&self, seed: u64, index: usize, _count: usize, dimension: usize) -> (f64, f64) {
+        let index = Self::shuffled_index(seed, index, dimension);
+        (
+            Self::scrambled(sobol_0(index), seed, dimension),
+            Self::scrambled(sobol_1(index), seed, dimension + 1),
+        )
+    }
+}
+
+/// First dimension of the Sobol sequence, the van der Corput sequence.
+fn sobol_0(index: u32) -> u32 {
+    index.reverse_bits()
+}
+
+/// Second dimension of the Sobol sequence.
+fn sobol_1(mut index: u32) -> u32 {
+    let mut value = 0;
+    let mut direction = 1u32 << 31;
+
+    while index != 0 {
+        if index & 1 != 0 {
+            value ^= direction;
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+
+    value
+}
+
+/// Owen scrambling of the bits of `x`, every bit is flipped based on the bits above it.
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50b47c);
+    x ^= x.wrapping_mul(0xb82f1e52);
+    x ^= x.wrapping_mul(0xc7afe638);
+    x ^= x.wrapping_mul(0x8d22f6e6);
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampler::{checks, pixel_seed};
+
+    #[test]
+    fn every_stratum_gets_one_sample() {
+        for seed in [pixel_seed(0, 0), pixel_seed(17, 3)] {
+            for k in 0..10 {
+                for dimension in 0..8 {
+                    assert!(checks::covers_strata(
+                        &SobolSampler,
+                        seed,
+                        1 << k,
+                        dimension
+                    ));
+                }
+            }
+            // pairs of dimensions stay stratified in every grid with a cell per sample
+            for k in 0..8 {
+                for kx in 0..=k {
+                    let grid = (1 << kx, 1 << (k - kx));
+                    for dimension in [0, 2, 6] {
+                        assert!(checks::covers_grid(&SobolSampler, seed, grid, dimension));
+                    }
+                }
+            }
+        }
+    }
+}
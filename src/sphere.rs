@@ -1,9 +1,11 @@
-use std::sync::Arc;
+use std::{f64::consts::PI, sync::Arc};
 
 use crate::{
     hittable::{HitRecord, HitResult, Hittable},
+    light::{cone_pdf, sample_in_cone},
     material::Material,
     ray::Ray,
+    sampler::SampleStream,
     vec3::{Point3, Vec3},
 };
 
@@ -29,6 +31,18 @@ impl Sphere {
     pub fn radius(&self) -> f64 {
         self.radius
     }
+
+    /// Cosine of the half angle of the cone in which the sphere is seen from a point at
+    /// `to_center` away from its center, `None` if the point lies inside the sphere.
+    fn cos_theta_max(&self, to_center: Vec3) -> Option<f64> {
+        let distance_squared = to_center.length_squared();
+        let radius_squared = self.radius() * self.radius();
+        if distance_squared <= radius_squared {
+            return None;
+        }
+
+        Some((1.0 - radius_squared / distance_squared).sqrt())
+    }
 }
 
 impl Hittable for Sphere {
@@ -56,10 +70,50 @@ impl Hittable for Sphere {
 
         rec.t = root;
         rec.p = r.at(rec.t);
-        rec.normal = (rec.p - self.center()) / self.radius();
         let outward_normal = (rec.p - self.center()) / self.radius();
         rec.set_front_face(r, outward_normal);
+        (rec.u, rec.v) = sphere_uv(outward_normal);
+        rec.object = self as *const Self as usize;
 
         HitResult::Hit(self.mat.clone())
     }
+
+    fn sample_direction(&self, origin: Point3, samples: &mut SampleStream) -> Option<Vec3> {
+        // only the cone of directions from origin that hit the sphere is sampled
+        let to_center = self.center() - origin;
+        let cos_theta_max = self.cos_theta_max(to_center)?;
+
+        Some(sample_in_cone(to_center, cos_theta_max, samples.get_2d()))
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let cos_theta_max = match self.cos_theta_max(self.center() - origin) {
+            Some(cos_theta_max) => cos_theta_max,
+            None => return 0.0,
+        };
+
+        let mut rec = HitRecord::default();
+        if self
+            .hit(
+                Ray::new(origin, direction, 0.0),
+                0.001,
+                f64::INFINITY,
+                &mut rec,
+            )
+            .is_miss()
+        {
+            return 0.0;
+        }
+
+        cone_pdf(cos_theta_max)
+    }
+}
+
+/// Maps a point on the unit sphere to `u` going around the y axis starting at -x and `v`
+/// from the bottom to the top.
+pub(crate) fn sphere_uv(p: Point3) -> (f64, f64) {
+    let theta = f64::acos((-p.y()).clamp(-1.0, 1.0));
+    let phi = f64::atan2(-p.z(), p.x()) + PI;
+
+    (phi / (2.0 * PI), theta / PI)
 }
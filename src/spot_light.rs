@@ -0,0 +1,82 @@
+use crate::{
+    light::{Falloff, Light, LightSample},
+    sampler::SampleStream,
+    utils::clamp,
+    vec3::{Color, Point3, Vec3},
+};
+
+/// Point light that only shines into a cone.
+pub struct SpotLight {
+    position: Point3,
+    direction: Vec3,
+    intensity: Color,
+    cos_outer: f64,
+    cos_inner: f64,
+    falloff: Falloff,
+}
+
+impl SpotLight {
+    /// `cone_angle` is the half angle of the cone in degrees, the light fades out over the
+    /// outermost `penumbra` degrees of it.
+    pub fn new(
+        position: Point3,
+        direction: Vec3,
+        intensity: Color,
+        cone_angle: f64,
+        penumbra: f64,
+    ) -> Self {
+        let penumbra = clamp(penumbra, 0.0, cone_angle);
+
+        Self {
+            position,
+            direction: Vec3::unit_vector(direction),
+            intensity,
+            cos_outer: cone_angle.to_radians().cos(),
+            cos_inner: (cone_angle - penumbra).to_radians().cos(),
+            falloff: Falloff::default(),
+        }
+    }
+
+    pub fn with_falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    /// Share of the intensity that leaves the light into `direction`.
+    fn cone_factor(&self, direction: Vec3) -> f64 {
+        let cos_theta = Vec3::dot(direction, self.direction);
+        if cos_theta <= self.cos_outer {
+            return 0.0;
+        }
+        if cos_theta >= self.cos_inner {
+            return 1.0;
+        }
+
+        // smooth fade between the inner and outer cone
+        let t = (cos_theta - self.cos_outer) / (self.cos_inner - self.cos_outer);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, p: Point3, _samples: &mut SampleStream) -> Option<LightSample> {
+        let to_light = self.position - p;
+        let distance = to_light.length();
+        if distance == 0.0 {
+            return None;
+        }
+
+        let direction = to_light / distance;
+        let cone_factor = self.cone_factor(-direction);
+        if cone_factor == 0.0 {
+            return None;
+        }
+
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: self.intensity * (cone_factor * self.falloff.attenuation(distance)),
+            pdf: 1.0,
+        })
+    }
+}
@@ -0,0 +1,117 @@
+use std::{cell::Cell, ops, time::Duration};
+
+/// Ray and intersection counts of a render.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RayCounters {
+    pub camera_rays: u64,
+    pub secondary_rays: u64,
+    pub shadow_rays: u64,
+    pub intersection_tests: u64,
+}
+
+impl ops::AddAssign<RayCounters> for RayCounters {
+    fn add_assign(&mut self, rhs: RayCounters) {
+        self.camera_rays += rhs.camera_rays;
+        self.secondary_rays += rhs.secondary_rays;
+        self.shadow_rays += rhs.shadow_rays;
+        self.intersection_tests += rhs.intersection_tests;
+    }
+}
+
+// every worker counts into its own copy, which is collected once per finished job
+thread_local! {
+    static COUNTERS: Cell<RayCounters> = Cell::new(RayCounters::default());
+}
+
+fn update(f: impl FnOnce(&mut RayCounters)) {
+    COUNTERS.with(|c| {
+        let mut counters = c.get();
+        f(&mut counters);
+        c.set(counters);
+    });
+}
+
+pub(crate) fn count_camera_ray() {
+    update(|c| c.camera_rays += 1);
+}
+
+pub(crate) fn count_secondary_ray() {
+    update(|c| c.secondary_rays += 1);
+}
+
+pub(crate) fn count_shadow_ray() {
+    update(|c| c.shadow_rays += 1);
+}
+
+pub(crate) fn count_intersection_tests(n: usize) {
+    update(|c| c.intersection_tests += n as u64);
+}
+
+/// Returns the counts of the current thread and resets them.
+pub(crate) fn take_thread_counters() -> RayCounters {
+    COUNTERS.with(|c| c.take())
+}
+
+/// Numbers collected during a render, useful to tune scenes.
+///
+/// The renderer only knows about the render itself, the time spent building the
+/// scene and writing the output has to be filled in by the caller.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderStats {
+    pub counters: RayCounters,
+    pub scene_build: Duration,
+    pub render: Duration,
+    pub output: Duration,
+}
+
+impl RenderStats {
+    /// Average number of segments of a path, including the camera ray but not the shadow rays.
+    pub fn average_path_length(&self) -> f64 {
+        if self.counters.camera_rays == 0 {
+            return 0.0;
+        }
+
+        (self.counters.camera_rays + self.counters.secondary_rays) as f64
+            / self.counters.camera_rays as f64
+    }
+
+    pub fn total_rays(&self) -> u64 {
+        self.counters.camera_rays + self.counters.secondary_rays + self.counters.shadow_rays
+    }
+
+    pub fn rays_per_second(&self) -> f64 {
+        let secs = self.render.as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+
+        self.total_rays() as f64 / secs
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            r#"{{
+  "camera_rays": {},
+  "secondary_rays": {},
+  "shadow_rays": {},
+  "intersection_tests": {},
+  "average_path_length": {:.4},
+  "rays_per_second": {:.1},
+  "time": {{
+    "scene_build": {:.6},
+    "render": {:.6},
+    "output": {:.6}
+  }}
+}}"#,
+            self.counters.camera_rays,
+            self.counters.secondary_rays,
+            self.counters.shadow_rays,
+            self.counters.intersection_tests,
+            self.average_path_length(),
+            self.rays_per_second(),
+            self.scene_build.as_secs_f64(),
+            self.render.as_secs_f64(),
+            self.output.as_secs_f64(),
+        )
+    }
+}
@@ -0,0 +1,125 @@
+use crate::sampler::{mix, to_unit, Sampler};
+
+/// Splits every dimension into as many strata as there are samples and puts one sample in
+/// each, pairs of dimensions are split into a jittered grid.
+///
+/// The strata are shuffled independently per pixel and dimension, so the dimensions don't
+/// correlate with each other.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StratifiedSampler;
+
+impl StratifiedSampler {
+    fn jitter(seed: u64, index: usize, dimension: usize) -> f64 {
+        to_unit(mix(mix(seed, index as u64), (dimension as u64) | (1 << 32)))
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn get_1d(&self, seed: u64, index: usize, count: usize, dimension: usize) -> f64 {
+        let count = count.max(1);
+        let stratum = permute(index % count, count, mix(seed, dimension as u64));
+
+        (stratum as f64 + Self::jitter(seed, index, dimension)) / count as f64
+    }
+
+    fn get_2d(&self, seed: u64, index: usize, count: usize, dimension: usize) -> (f64, f64) {
+        let nx = ((count.max(1) as f64).sqrt() as usize).max(1);
+        let ny = count.max(1).div_ceil(nx);
+
+        // with a count that isn't a square some cells of the grid stay empty
+        let cell = permute(index % (nx * ny), nx * ny, mix(seed, dimension as u64));
+        let (cx, cy) = (cell % nx, cell / nx);
+
+        (
+            (cx as f64 + Self::jitter(seed, index, dimension)) / nx as f64,
+            (cy as f64 + Self::jitter(seed, index, dimension + 1)) / ny as f64,
+        )
+    }
+}
+
+/// Position of `i` in a random permutation of `0..len` chosen by `seed`, after Kensler,
+/// "Correlated Multi-Jittered Sampling" (2013).
+pub(crate) fn permute(i: usize, len: usize, seed: u64) -> usize {
+    let (l, p) = (len as u32, seed as u32);
+    let mut i = i as u32;
+
+    let mut w = l.wrapping_sub(1);
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    // hashes within the next power of two and walks the cycle until it lands inside len
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | p >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+
+        if i < l {
+            break;
+        }
+    }
+
+    (i.wrapping_add(p) % l) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampler::{checks, pixel_seed};
+
+    #[test]
+    fn every_stratum_gets_one_sample() {
+        for seed in [pixel_seed(0, 0), pixel_seed(17, 3)] {
+            for count in [1, 2, 7, 16, 64, 100] {
+                for dimension in 0..8 {
+                    assert!(checks::covers_strata(
+                        &StratifiedSampler,
+                        seed,
+                        count,
+                        dimension
+                    ));
+                }
+            }
+            for n in [1, 2, 4, 8, 10] {
+                for dimension in 0..8 {
+                    assert!(checks::covers_grid(
+                        &StratifiedSampler,
+                        seed,
+                        (n, n),
+                        dimension
+                    ));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn permute_is_a_permutation() {
+        for len in [1, 2, 5, 16, 100] {
+            for seed in [0, 1, 0xdead_beef] {
+                let mut hit = vec![false; len];
+                for i in 0..len {
+                    hit[permute(i, len, seed)] = true;
+                }
+                assert!(hit.iter().all(|&hit| hit), "{len}, {seed}");
+            }
+        }
+    }
+}
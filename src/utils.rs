@@ -1,5 +1,3 @@
-use rand::{thread_rng, Rng};
-
 pub fn clamp<T: std::cmp::PartialOrd>(x: T, min: T, max: T) -> T {
     if x < min {
         min
@@ -9,9 +7,3 @@ pub fn clamp<T: std::cmp::PartialOrd>(x: T, min: T, max: T) -> T {
         x
     }
 }
-
-pub fn random_in_range(min: f64, max: f64) -> f64 {
-    let mut rng = thread_rng();
-
-    rng.gen_range(min..max)
-}
@@ -58,27 +58,35 @@ impl Vec3 {
         )
     }
 
-    pub fn random_in_unit_sphere() -> Vec3 {
-        loop {
-            let p = Vec3::random(-1.0, 1.0);
-            if p.length_squared() >= 1.0 {
-                continue;
-            }
-            return p;
-        }
+    /// Maps `u` from the unit square to a direction around the z axis, distributed with
+    /// density cos(theta) / pi.
+    pub fn sample_cosine_direction(u: (f64, f64)) -> Vec3 {
+        let phi = 2.0 * std::f64::consts::PI * u.0;
+        let r = u.1.sqrt();
+
+        Vec3(phi.cos() * r, phi.sin() * r, (1.0 - u.1).sqrt())
     }
 
-    pub fn random_unit_vector() -> Vec3 {
-        Vec3::unit_vector(Vec3::random_in_unit_sphere())
+    /// Maps `u` from the unit square uniformly onto the unit sphere.
+    pub fn sample_unit_vector(u: (f64, f64)) -> Vec3 {
+        let z = 1.0 - 2.0 * u.0;
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        let phi = 2.0 * std::f64::consts::PI * u.1;
+
+        Vec3(r * phi.cos(), r * phi.sin(), z)
     }
 
-    pub fn random_in_hemisphere(normal: Vec3) -> Vec3 {
-        let in_unit_sphere = Vec3::random_in_unit_sphere();
-        if Vec3::dot(in_unit_sphere, normal) > 0.0 {
-            in_unit_sphere
-        } else {
-            -in_unit_sphere
-        }
+    /// Maps `u` from the unit square and `w` uniformly into the unit ball.
+    pub fn sample_in_unit_sphere(u: (f64, f64), w: f64) -> Vec3 {
+        w.cbrt() * Vec3::sample_unit_vector(u)
+    }
+
+    /// Maps `u` from the unit square uniformly onto the unit disk in the xy plane.
+    pub fn sample_in_unit_disk(u: (f64, f64)) -> Vec3 {
+        let r = u.0.sqrt();
+        let phi = 2.0 * std::f64::consts::PI * u.1;
+
+        Vec3(r * phi.cos(), r * phi.sin(), 0.0)
     }
 
     pub fn near_zero(&self) -> bool {
@@ -86,6 +94,20 @@ impl Vec3 {
         (self.x().abs() < s) && (self.y().abs() < s) && (self.z().abs() < s)
     }
 
+    pub fn max_component(&self) -> f64 {
+        f64::max(self.0, f64::max(self.1, self.2))
+    }
+
+    /// Replaces negative components with zero.
+    pub fn max_zero(&self) -> Vec3 {
+        Vec3(self.0.max(0.0), self.1.max(0.0), self.2.max(0.0))
+    }
+
+    /// Perceived brightness of a linear sRGB color.
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.0 + 0.7152 * self.1 + 0.0722 * self.2
+    }
+
     pub fn reflect(v: Vec3, n: Vec3) -> Vec3 {
         v - (2.0 * Vec3::dot(v, n)) * n
     }
@@ -96,18 +118,6 @@ impl Vec3 {
         let r_out_parllel = -((1.0 - r_out_perp.length_squared()).abs()).sqrt() * n;
         r_out_perp + r_out_parllel
     }
-
-    pub fn random_in_unit_disk() -> Vec3 {
-        let mut rng = rand::thread_rng();
-
-        loop {
-            let p = Vec3(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0);
-            if p.length_squared() >= 1.0 {
-                continue;
-            }
-            return p;
-        }
-    }
 }
 
 impl ops::Neg for Vec3 {
@@ -134,6 +144,14 @@ impl ops::MulAssign<f64> for Vec3 {
     }
 }
 
+impl ops::MulAssign<Vec3> for Vec3 {
+    fn mul_assign(&mut self, rhs: Vec3) {
+        self.0 *= rhs.0;
+        self.1 *= rhs.1;
+        self.2 *= rhs.2;
+    }
+}
+
 impl ops::DivAssign<f64> for Vec3 {
     fn div_assign(&mut self, rhs: f64) {
         *self *= 1.0 / rhs